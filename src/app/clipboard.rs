@@ -0,0 +1,23 @@
+use base64::Engine;
+use std::io::{self, Write};
+
+/// Copies text to the system clipboard using the OSC 52 terminal escape sequence.
+/// This works over SSH and inside tmux (with `set-clipboard on`) without any
+/// platform clipboard libraries.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()
+}
+
+/// Formats a note as a markdown link, `[title](path)`.
+/// Paths containing spaces are wrapped in angle brackets so the link still parses.
+pub fn markdown_link(title: &str, path: &str) -> String {
+    let title = title.replace('[', "\\[").replace(']', "\\]");
+    if path.contains(' ') {
+        format!("[{}](<{}>)", title, path)
+    } else {
+        format!("[{}]({})", title, path)
+    }
+}
@@ -1,5 +1,5 @@
-use crate::app::core::{App, AppState};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::core::{App, AppState, InputMode};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io::Stdout;
@@ -11,8 +11,37 @@ pub struct CommandItem {
     pub name: &'static str,
     pub description: &'static str,
     pub action: CommandAction,
+    // Sub-commands; a command with children opens them instead of running `action`
+    pub children: Vec<CommandItem>,
 }
 
+impl CommandItem {
+    /// A command that only groups other commands.
+    pub fn category(
+        name: &'static str,
+        description: &'static str,
+        children: Vec<CommandItem>,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            action: Box::new(|_app, _terminal| {}),
+            children,
+        }
+    }
+}
+
+/// A parent palette level, saved while browsing one of its categories.
+pub struct PaletteLevel {
+    pub name: &'static str,
+    pub items: Vec<CommandItem>,
+    // Position of the opened category in `items`
+    pub category_index: usize,
+}
+
+/// Number of notes previewed by the inline search under the "Search" command.
+const PALETTE_SEARCH_LIMIT: usize = 5;
+
 type Type = *const Box<
     dyn Fn(
             &mut crate::app::core::App,
@@ -26,9 +55,24 @@ pub fn handle_command_palette_key(
     key: KeyEvent,
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
 ) {
+    if app.palette_search_active {
+        handle_palette_search_key(app, key);
+        return;
+    }
+
     match key.code {
         KeyCode::Esc => {
-            app.state = AppState::Preview;
+            // Clear the filter first, then step out of categories, then close
+            if !app.palette_filter.is_empty() {
+                app.palette_filter.clear();
+                app.selected_command_index = 0;
+            } else if !leave_category(app) {
+                app.state = AppState::Preview;
+            }
+        }
+        KeyCode::Tab if is_search_highlighted(app) => {
+            // Open the inline search launcher under the "Search" command
+            app.palette_search_active = true;
         }
         KeyCode::Up => {
             if app.selected_command_index > 0 {
@@ -36,12 +80,38 @@ pub fn handle_command_palette_key(
             }
         }
         KeyCode::Down => {
-            if app.selected_command_index + 1 < app.command_items.len() {
+            if app.selected_command_index + 1 < visible_commands(app).len() {
                 app.selected_command_index += 1;
             }
         }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.palette_filter.push(c);
+            app.selected_command_index = 0;
+        }
+        KeyCode::Backspace => {
+            app.palette_filter.pop();
+            app.selected_command_index = 0;
+        }
         KeyCode::Enter => {
-            if let Some(cmd) = app.command_items.get(app.selected_command_index) {
+            let Some(&index) = visible_commands(app).get(app.selected_command_index) else {
+                return;
+            };
+            if !app.command_items[index].children.is_empty() {
+                let target = alias_target(app).map(str::to_string);
+                enter_category(app, index);
+                // An alias of a nested command carries on down to it
+                if let Some(target) = target {
+                    if let Some(position) = visible_commands(app)
+                        .iter()
+                        .position(|&i| leads_to(&app.command_items[i], &target))
+                    {
+                        app.selected_command_index = position;
+                        handle_command_palette_key(app, key, terminal);
+                    }
+                }
+                return;
+            }
+            if let Some(cmd) = app.command_items.get(index) {
                 // Use a raw pointer to call the closure to avoid borrow conflicts.
                 let action_ptr: Type = &cmd.action as *const _;
                 unsafe {
@@ -52,3 +122,181 @@ pub fn handle_command_palette_key(
         _ => {}
     }
 }
+
+/// Commands left out of the list until something is typed into the filter.
+const HIDDEN_COMMANDS: &[&str] = &["Benchmark"];
+
+/// Built-in short names for frequent commands; `command_aliases` adds more.
+const DEFAULT_ALIASES: &[(&str, &str)] = &[
+    ("idx", "Index Vectors"),
+    ("rb", "Rebuild Search Index"),
+    ("ss", "Saved Searches"),
+    ("gp", "Go to Path"),
+    ("cfg", "Open Config Editor"),
+];
+
+/// The command the palette filter is an alias of, if it is one.
+fn alias_target(app: &App) -> Option<&str> {
+    let alias = app.palette_filter.to_lowercase();
+    if let Some(name) = app.settings.command_aliases.get(&alias) {
+        return Some(name.as_str());
+    }
+    DEFAULT_ALIASES
+        .iter()
+        .find(|(short, _)| *short == alias)
+        .map(|(_, name)| *name)
+}
+
+/// True when `cmd` is the command called `name`, or a category holding it.
+fn leads_to(cmd: &CommandItem, name: &str) -> bool {
+    cmd.name.eq_ignore_ascii_case(name) || cmd.children.iter().any(|child| leads_to(child, name))
+}
+
+/// Indices into `command_items` of the commands matching the palette filter.
+/// The command an alias names, or the category holding it, comes first, and
+/// commands matched only by their description come after those matched by name.
+pub fn visible_commands(app: &App) -> Vec<usize> {
+    let filter = &app.palette_filter;
+    let candidates = app
+        .command_items
+        .iter()
+        .enumerate()
+        .filter(|(_, cmd)| !filter.is_empty() || !HIDDEN_COMMANDS.contains(&cmd.name));
+    let (by_name, rest): (Vec<_>, Vec<_>) =
+        candidates.partition(|(_, cmd)| fuzzy_match(cmd.name, filter));
+    let mut visible: Vec<usize> = by_name
+        .into_iter()
+        .chain(
+            rest.into_iter()
+                .filter(|(_, cmd)| fuzzy_match(cmd.description, filter)),
+        )
+        .map(|(i, _)| i)
+        .collect();
+    let aliased = alias_target(app).and_then(|target| {
+        app.command_items
+            .iter()
+            .position(|cmd| leads_to(cmd, target))
+    });
+    if let Some(index) = aliased {
+        visible.retain(|&i| i != index);
+        visible.insert(0, index);
+    }
+    visible
+}
+
+/// True when every character of `filter` appears in `text` in order, ignoring case.
+fn fuzzy_match(text: &str, filter: &str) -> bool {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|f| text_chars.any(|c| c == f))
+}
+
+/// Replaces the command list with the children of the category at `index`.
+fn enter_category(app: &mut App, index: usize) {
+    let children = std::mem::take(&mut app.command_items[index].children);
+    let items = std::mem::replace(&mut app.command_items, children);
+    let name = items[index].name;
+    app.palette_stack.push(PaletteLevel {
+        name,
+        items,
+        category_index: index,
+    });
+    app.palette_filter.clear();
+    app.selected_command_index = 0;
+}
+
+/// Goes back up one level, returning false when already at the top.
+fn leave_category(app: &mut App) -> bool {
+    let Some(mut parent) = app.palette_stack.pop() else {
+        return false;
+    };
+    let children = std::mem::take(&mut app.command_items);
+    parent.items[parent.category_index].children = children;
+    app.command_items = parent.items;
+    app.palette_filter.clear();
+    app.selected_command_index = visible_commands(app)
+        .iter()
+        .position(|&i| i == parent.category_index)
+        .unwrap_or(0);
+    true
+}
+
+/// Breadcrumb of the categories opened so far, e.g. "Indexing".
+pub fn palette_path(app: &App) -> String {
+    app.palette_stack
+        .iter()
+        .map(|level| level.name)
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+/// Returns true when the highlighted command is the "Search" entry.
+pub fn is_search_highlighted(app: &App) -> bool {
+    visible_commands(app)
+        .get(app.selected_command_index)
+        .and_then(|&i| app.command_items.get(i))
+        .is_some_and(|cmd| cmd.name == "Search")
+}
+
+/// Handles keys while typing into the palette's inline search launcher.
+fn handle_palette_search_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            // Back to the command list, keeping the query for a quick retry
+            app.palette_search_active = false;
+        }
+        KeyCode::Char(c) => {
+            app.palette_search_query.push(c);
+            run_palette_search(app);
+        }
+        KeyCode::Backspace => {
+            app.palette_search_query.pop();
+            run_palette_search(app);
+        }
+        KeyCode::Up => {
+            if app.palette_search_selected > 0 {
+                app.palette_search_selected -= 1;
+            }
+        }
+        KeyCode::Down => {
+            if app.palette_search_selected + 1 < app.palette_search_results.len() {
+                app.palette_search_selected += 1;
+            }
+        }
+        KeyCode::Enter => {
+            // Jump straight into the search view with the chosen note selected
+            if app.palette_search_selected < app.palette_search_results.len() {
+                app.search_query = std::mem::take(&mut app.palette_search_query);
+                app.search_results = std::mem::take(&mut app.palette_search_results);
+                app.selected_search_index = app.palette_search_selected;
+                app.palette_search_active = false;
+                app.input_mode = InputMode::Normal;
+                app.state = AppState::Search;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Runs a small search for the inline launcher and stores the top matches.
+fn run_palette_search(app: &mut App) {
+    app.palette_search_selected = 0;
+    if app.palette_search_query.is_empty() {
+        app.palette_search_results.clear();
+        return;
+    }
+
+    if let Some(ref search_engine) = app.search_engine {
+        match search_engine.search(&app.palette_search_query, PALETTE_SEARCH_LIMIT) {
+            Ok(results) => app.palette_search_results = results,
+            Err(e) => {
+                app.set_status(format!("Search error: {}", e));
+                app.palette_search_results.clear();
+            }
+        }
+    } else {
+        app.palette_search_results.clear();
+    }
+}
@@ -1,4 +1,5 @@
 use crate::app::command_palette::CommandItem;
+use crate::app::settings::{EditingEnter, Settings, StartupAction};
 use ratatui::widgets::Block;
 
 use crate::app::ui::{draw_command_palette, draw_search_ui};
@@ -13,8 +14,11 @@ use notemancy_core::scan::{ScannedFile, Scanner};
 use notemancy_core::search::{SearchEngine, SearchResult};
 use ratatui::style::{Color, Style};
 use std::{
+    collections::{HashMap, HashSet},
     io::Stdout,
-    sync::mpsc::{self, Receiver, TryRecvError},
+    sync::atomic::{AtomicU64, Ordering},
+    sync::mpsc::{self, Receiver, Sender},
+    sync::Arc,
     thread,
     time::{Duration, Instant},
 };
@@ -25,6 +29,54 @@ pub enum DetailViewMode {
     RelatedFiles,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    Lexical,  // Full-text search through the search engine
+    Semantic, // Embedding similarity against the raw query text
+}
+
+/// Metric used to rank related files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimilarityMetric {
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl SimilarityMetric {
+    pub fn label(self) -> &'static str {
+        match self {
+            SimilarityMetric::Cosine => "cosine",
+            SimilarityMetric::Dot => "dot",
+            SimilarityMetric::Euclidean => "euclidean",
+        }
+    }
+}
+
+/// Metrics `notemancy_core::ai::AI::find_similar_documents` can rank by. The core
+/// currently only returns cosine distances, so cycling is a no-op until it
+/// exposes a metric parameter; add entries here when it does.
+pub const SUPPORTED_METRICS: &[SimilarityMetric] = &[SimilarityMetric::Cosine];
+
+/// How the related files list is ordered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelatedRanking {
+    Embedding, // Pure embedding similarity
+    Hybrid,    // Embedding similarity blended with shared terms and tags
+}
+
+impl RelatedRanking {
+    pub fn label(self) -> &'static str {
+        match self {
+            RelatedRanking::Embedding => "embedding",
+            RelatedRanking::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// Weight of lexical overlap in the hybrid related-files score.
+const HYBRID_LEXICAL_WEIGHT: f32 = 0.3;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputMode {
     Normal,  // Navigation mode where shortcuts work
@@ -40,10 +92,82 @@ pub enum AppState {
     Search,
     CommandPalette,
     IndexingVectors,
+    ConfigPreview,
+}
+
+/// Sends `event` if the worker thread holding it panics, so whatever is waiting
+/// on the worker is reset the same way as for a reported failure.
+struct PanicReport {
+    tx: Sender<AppEvent>,
+    event: Option<AppEvent>,
+}
+
+impl PanicReport {
+    fn new(tx: &Sender<AppEvent>, event: AppEvent) -> Self {
+        Self {
+            tx: tx.clone(),
+            event: Some(event),
+        }
+    }
+}
+
+impl Drop for PanicReport {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            if let Some(event) = self.event.take() {
+                let _ = self.tx.send(event);
+            }
+        }
+    }
 }
 
-type ScanReceiver = Option<Receiver<Result<(Vec<ScannedFile>, String), Report>>>;
-type IndexReceiver = Option<std::sync::mpsc::Receiver<()>>;
+/// Messages sent from background workers to the main loop.
+///
+/// Every worker shares one channel, so results are handled in one place and
+/// replies for a query or document that is no longer current can be dropped.
+pub enum AppEvent {
+    SearchEngineReady(Result<SearchEngine, String>),
+    ScanFinished(Result<(Vec<ScannedFile>, String), Report>),
+    // Full-text indexing is done; carries the error message when it failed
+    IndexingFinished(Option<String>),
+    VectorIndexStatus(String),
+    Status(String),
+    RelatedFiles {
+        path: String,
+        result: Result<Vec<SearchResult>, String>,
+        // Sent by the background warm-up rather than a lookup the user is waiting for
+        prewarm: bool,
+        // Value of `App::related_generation` when the lookup started
+        generation: u64,
+    },
+    SemanticResults {
+        query: String,
+        result: Result<Vec<SearchResult>, String>,
+        // Value of `App::semantic_generation` when the search started
+        generation: u64,
+    },
+    // Text of a note read for the preview pane
+    PreviewContent {
+        path: String,
+        modified: Option<std::time::SystemTime>,
+        content: String,
+    },
+    // Timing of each benchmark step, in the order they ran
+    BenchmarkFinished(Vec<(String, Result<Duration, String>)>),
+    // Every frontmatter tag with the notes carrying it, for the tag browser
+    TagIndex(Vec<(String, Vec<String>)>),
+    // Scanned notes each index didn't return, from the Index Health Check
+    IndexHealth {
+        total: usize,
+        missing_full_text: Result<Vec<String>, String>,
+        missing_vectors: Result<Vec<String>, String>,
+    },
+    // Match contexts for the results of `query`, keyed by note path
+    ResultSnippets {
+        query: String,
+        snippets: HashMap<String, String>,
+    },
+}
 
 pub struct App {
     pub running: bool,
@@ -52,39 +176,311 @@ pub struct App {
     pub spinner_chars: Vec<char>,
     pub scan_result: Option<Vec<ScannedFile>>,
     pub scan_summary: Option<String>,
-    pub scanning_receiver: ScanReceiver,
+    // Configured vault directory, or the scanned notes' common ancestor; set per scan
+    pub vault_root: std::path::PathBuf,
+    pub scan_started: Instant,
+    // The running scan thread; cleared once its result arrives
+    pub scan_thread: Option<thread::JoinHandle<()>>,
     pub last_tick: Instant,
     // For search mode:
     pub search_query: String,
     pub search_results: Vec<SearchResult>,
     pub selected_search_index: usize,
+    // Scroll offset of the results list, kept between frames so the selection stays visible
+    pub results_list_state: ratatui::widgets::ListState,
+    pub search_mode: SearchMode,
+    // Match contexts extracted from the files of lexical results, keyed by path
+    pub result_snippets: HashMap<String, String>,
+    pub is_semantic_searching: bool,
+    // Bumped whenever the results a semantic search would fill are replaced;
+    // workers holding an older value drop their results
+    pub semantic_generation: Arc<AtomicU64>,
+    pub semantic_search_error: Option<String>,
     // Store the search interface.
     pub search_engine: Option<SearchEngine>,
-    pub indexing_receiver: IndexReceiver,
+    // Notes the index being built will cover, and when the build started
+    pub indexing_total: usize,
+    pub indexing_started: Instant,
+    // Where to go once indexing finishes; `None` means the search view
+    pub indexing_return_state: Option<AppState>,
     pub detail_view_mode: DetailViewMode,
     pub related_files: Vec<SearchResult>,
     pub input_mode: InputMode,
 
     pub is_loading_related_files: bool,
-    pub related_files_receiver: Option<
-        std::sync::mpsc::Receiver<Result<Vec<notemancy_core::search::SearchResult>, String>>,
-    >,
     pub related_files_error: Option<String>,
+    // Highlighted related file; the preview's position is `preview_scroll`
+    pub related_selected: usize,
+    // Related files by note path, from lookups and the top-result warm-up
+    pub related_cache: HashMap<String, Vec<SearchResult>>,
+    // Note whose related files are being warmed up in the background
+    pub prewarming_path: Option<String>,
+    // Rendered preview of the highlighted related file, keyed by its path
+    pub related_preview: Option<(String, Vec<ratatui::text::Line<'static>>)>,
+    // Frontmatter priority per note path, with the modification time it was read at
+    pub priority_cache: HashMap<String, (Option<std::time::SystemTime>, Option<f32>)>,
+    // Frontmatter tags per note path for `result_tags`, cached the same way
+    pub tag_cache: HashMap<String, (Option<std::time::SystemTime>, Vec<String>)>,
+    // `accent_color` resolved to a color; `vault` needs a scan first
+    pub accent: Option<Color>,
+    // How many results the current query asks for; grows with "load more"
+    pub result_limit: usize,
+    pub similarity_metric: SimilarityMetric,
+    pub related_ranking: RelatedRanking,
+    // Shared-term overlap (0..1) between the selected note and each related file
+    pub related_overlap: HashMap<String, f32>,
     // Command palette fields:
     pub command_items: Vec<CommandItem>,
     pub selected_command_index: usize,
+    // Find-as-you-type filter for the current palette level
+    pub palette_filter: String,
+    // Parent levels while browsing a command category
+    pub palette_stack: Vec<crate::app::command_palette::PaletteLevel>,
+    // State the palette was opened from
+    pub palette_return_state: AppState,
+    // Inline search launcher shown under the palette's "Search" command:
+    pub palette_search_active: bool,
+    pub palette_search_query: String,
+    pub palette_search_results: Vec<SearchResult>,
+    pub palette_search_selected: usize,
     pub vector_indexing_status: Option<String>, // To display status messages during vector indexing
     pub vector_indexing_complete: bool,         // Flag to indicate when indexing is complete
     pub vector_indexing_success_time: Option<Instant>,
-    pub vector_indexing_receiver: Option<std::sync::mpsc::Receiver<String>>,
     pub last_selected_index: usize,
     pub last_selection_change: std::time::Instant,
     pub debounce_duration: std::time::Duration,
     pub current_related_document_path: Option<String>,
+    // Bumped when the selection moves away from a running lookup; workers holding
+    // an older value drop their results instead of sending them
+    pub related_generation: Arc<AtomicU64>,
+    // Jump list of settled selections; `nav_cursor` entries are behind/at the current one
+    pub nav_history: Vec<NavEntry>,
+    pub nav_cursor: usize,
+    // Queries replaced by searching from a result's title, most recent last
+    pub previous_queries: Vec<String>,
+    // When each note was last snapshotted by Index Vectors, for its index status
+    pub indexed_times: HashMap<String, std::time::SystemTime>,
+    // An Index Health Check is probing the indexes
+    pub index_health_running: bool,
+    // Queries run with Enter, oldest first, and the one recalled with Up/Down
+    pub search_history: Vec<String>,
+    pub history_cursor: Option<usize>,
+    // Notes previewed in this or an earlier session, when `track_seen` is on
+    pub seen_notes: HashSet<String>,
+    // Start vector indexing once the running full-text index finishes
+    pub reembed_after_index: bool,
+    pub preview_scroll: u16,
+    pub preview_layout: PreviewLayout,
+    // Diff of a note against its snapshot from the last vector index, keyed by path
+    pub preview_diff: Option<(String, Vec<crate::app::diff::DiffLine>)>,
+    // Dims the input and results so the preview stands out
+    pub focus_mode: bool,
+    // Folder tree sidebar, built from the scan the first time it is shown
+    pub sidebar_visible: bool,
+    pub sidebar_focused: bool,
+    pub folder_tree: Option<crate::app::tree::FolderTree>,
+    pub tree_expanded: HashSet<std::path::PathBuf>,
+    pub tree_selected: usize,
+    // Transient message shown in the status line, with the time it was set
+    pub status_message: Option<(String, Instant)>,
+    pub settings: Settings,
+    // Code theme in use; starts as the configured one and is only saved on request
+    pub code_theme: String,
+    // Redraw throttling: only draw when something changed, at most once per interval
+    pub needs_redraw: bool,
+    pub last_draw: Instant,
+    // Popup listing the URLs found in the previewed note
+    pub link_picker: Option<LinkPicker>,
+    // One-line text input shown over any screen
+    pub text_prompt: Option<TextPrompt>,
+    pub confirmation: Option<Confirmation>,
+    // Index into `settings.saved_searches` while that picker is open
+    pub saved_search_selected: Option<usize>,
+    // Tags with their notes, opened from "Browse Tags" in the palette
+    pub tag_browser: Option<TagBrowser>,
+    // Popup listing the selected note's outbound links and backlinks
+    pub link_graph: Option<LinkGraph>,
+    pub quick_look: Option<QuickLook>,
+    pub preview_content: Option<PreviewContent>,
+    // Note the preview pane stays on while the selection moves, set with `p`
+    pub preview_locked_path: Option<String>,
+    // Notes passed on the command line or stdin, shown instead of the startup scan
+    pub seeded_results: Option<Vec<SearchResult>>,
+    // "Insert link into..." picker for the selected note
+    pub link_inserter: Option<LinkInserter>,
+    // Path being read for the preview pane, so a slow read isn't started twice
+    pub preview_loading: Option<String>,
+    // Set every tick; the previewed note is then checked once for outside edits
+    pub preview_check_due: bool,
+    // Report of the last "Benchmark" run, shown until a key is pressed
+    pub benchmark_report: Option<Vec<(String, Result<Duration, String>)>>,
+    // Index into `settings.send_to` while the "send to" menu is open
+    pub send_to_selected: Option<usize>,
+    pub config_preview: Option<ConfigPreview>,
+    // When Esc was last pressed, to detect a double Esc
+    pub last_esc: Option<Instant>,
+    // Label shown in place of the query when the results come from a command
+    pub result_list_title: Option<String>,
+    // Channel shared by all background workers
+    pub event_tx: Sender<AppEvent>,
+    pub event_rx: Receiver<AppEvent>,
+}
+
+/// Geometry of the last drawn preview, used to page through it by rows.
+#[derive(Debug, Default)]
+pub struct PreviewLayout {
+    // Wrapped row each heading starts on
+    pub heading_rows: Vec<usize>,
+    pub total_rows: usize,
+    pub page_height: usize,
+}
+
+/// A note visited in the search view, with enough context to return to it.
+pub struct NavEntry {
+    pub path: String,
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub selected: usize,
+    pub result_list_title: Option<String>,
+}
+
+/// Read-only view of the config file shown before opening the editor.
+pub struct ConfigPreview {
+    pub path: String,
+    pub lines: Vec<ratatui::text::Line<'static>>,
+    pub scroll: u16,
+}
+
+/// Text of the note shown in the preview pane, tagged with the file it was read
+/// from so it is never drawn for a different selection.
+pub struct PreviewContent {
+    pub path: String,
+    pub modified: Option<std::time::SystemTime>,
+    pub content: String,
+    // `content` as last drawn, so unchanged frames skip the markdown and syntect passes
+    pub rendered: Option<CachedRender>,
+}
+
+/// Rendered preview lines with the options they were rendered with.
+pub struct CachedRender {
+    pub html_mode: crate::app::settings::HtmlMode,
+    pub theme: String,
+    pub join_soft_breaks: bool,
+    pub code_width: Option<usize>,
+    pub markdown: crate::app::highlight::RenderedMarkdown,
+}
+
+impl CachedRender {
+    pub fn new(
+        options: crate::app::highlight::RenderOptions,
+        markdown: crate::app::highlight::RenderedMarkdown,
+    ) -> Self {
+        Self {
+            html_mode: options.html_mode,
+            theme: options.theme.to_string(),
+            join_soft_breaks: options.join_soft_breaks,
+            code_width: options.code_width,
+            markdown,
+        }
+    }
+
+    /// Whether rendering again with `options` would give the same lines.
+    pub fn rendered_with(&self, options: &crate::app::highlight::RenderOptions) -> bool {
+        self.html_mode == options.html_mode
+            && self.theme == options.theme
+            && self.join_soft_breaks == options.join_soft_breaks
+            && self.code_width == options.code_width
+    }
+}
+
+/// A large, scrollable preview of the selected result, drawn over the search view.
+pub struct QuickLook {
+    pub title: String,
+    pub lines: Vec<ratatui::text::Line<'static>>,
+    pub scroll: u16,
+    // Lines holding the search query, and which of them n/N last moved to
+    pub matches: Vec<usize>,
+    pub current_match: usize,
+    // Line to scroll to on the next draw, which knows how lines wrap
+    pub jump_to: Option<usize>,
+}
+
+/// State of the "open links in this note" popup.
+pub struct LinkPicker {
+    pub links: Vec<String>,
+    pub selected: usize,
+    // Entries are `[[wikilink]]` targets to jump to rather than URLs to open
+    pub wikilinks: bool,
+}
+
+/// Mini search for the note that should get a link to `source` appended.
+pub struct LinkInserter {
+    pub source: String,
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub selected: usize,
+}
+
+/// A note's direct neighbourhood: the notes it links to and the notes linking to it.
+pub struct LinkGraph {
+    pub path: String,
+    pub outbound: Vec<String>,
+    pub inbound: Vec<String>,
+    // Index over `outbound` followed by `inbound`
+    pub selected: usize,
+}
+
+impl LinkGraph {
+    /// Every entry in display order.
+    pub fn entries(&self) -> impl Iterator<Item = &String> {
+        self.outbound.iter().chain(&self.inbound)
+    }
+}
+
+/// What a text prompt does with its input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptAction {
+    AppendToDailyNote,
+    ExportResults(crate::app::export::ExportFormat),
+    GoToPath,
+    SaveSearch,
+    ModifiedSince,
+    StartWriting,
+}
+
+/// Actions that ask for confirmation first, as `confirm_style` says.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfirmAction {
+    RebuildSearchIndex,
+    IndexVectors,
+    ForgetDeletedSnapshots,
+    RescanVault,
+    ReconcileIndexes { full_text: bool, vectors: bool },
+}
+
+/// The tag browser popup. Tags are sorted by how many notes carry them.
+pub struct TagBrowser {
+    pub tags: Vec<(String, Vec<String>)>,
+    pub selected: usize,
+}
+
+/// A pending confirmation; `input` collects the typed word for `type_yes`.
+pub struct Confirmation {
+    pub prompt: String,
+    pub action: ConfirmAction,
+    pub input: String,
+}
+
+/// State of a one-line text prompt.
+pub struct TextPrompt {
+    pub title: String,
+    pub input: String,
+    pub action: PromptAction,
 }
 
 impl Default for App {
     fn default() -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
         Self {
             running: false,
             state: AppState::Starting,
@@ -92,42 +488,734 @@ impl Default for App {
             spinner_chars: vec!['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'],
             scan_result: None,
             scan_summary: None,
-            scanning_receiver: None,
+            vault_root: std::path::PathBuf::new(),
+            scan_started: Instant::now(),
+            scan_thread: None,
             last_tick: Instant::now(),
             search_query: String::new(),
             search_results: Vec::new(),
             selected_search_index: 0,
+            results_list_state: ratatui::widgets::ListState::default(),
+            search_mode: SearchMode::Lexical,
+            result_snippets: HashMap::new(),
+            is_semantic_searching: false,
+            semantic_generation: Arc::new(AtomicU64::new(0)),
+            semantic_search_error: None,
             search_engine: None,
-            indexing_receiver: None,
+            indexing_total: 0,
+            indexing_started: Instant::now(),
+            indexing_return_state: None,
             command_items: Vec::new(),
             selected_command_index: 0,
+            palette_filter: String::new(),
+            palette_stack: Vec::new(),
+            palette_return_state: AppState::Preview,
+            palette_search_active: false,
+            palette_search_query: String::new(),
+            palette_search_results: Vec::new(),
+            palette_search_selected: 0,
             vector_indexing_status: None,
             vector_indexing_complete: false,
             vector_indexing_success_time: None,
-            vector_indexing_receiver: None,
             detail_view_mode: DetailViewMode::Preview,
             related_files: Vec::new(),
             input_mode: InputMode::Editing,
             is_loading_related_files: false,
-            related_files_receiver: None,
             related_files_error: None,
+            related_selected: 0,
+            related_cache: HashMap::new(),
+            prewarming_path: None,
+            related_preview: None,
+            priority_cache: HashMap::new(),
+            tag_cache: HashMap::new(),
+            accent: None,
+            result_limit: Settings::default().search_limit,
+            similarity_metric: SimilarityMetric::Cosine,
+            related_ranking: RelatedRanking::Embedding,
+            related_overlap: HashMap::new(),
             last_selected_index: 0,
             last_selection_change: Instant::now(),
             debounce_duration: Duration::from_millis(1000), // 500ms debounce
             current_related_document_path: None,
+            related_generation: Arc::new(AtomicU64::new(0)),
+            nav_history: Vec::new(),
+            previous_queries: Vec::new(),
+            indexed_times: HashMap::new(),
+            index_health_running: false,
+            search_history: Vec::new(),
+            history_cursor: None,
+            seen_notes: HashSet::new(),
+            reembed_after_index: false,
+            nav_cursor: 0,
+            preview_scroll: 0,
+            preview_layout: PreviewLayout::default(),
+            preview_diff: None,
+            focus_mode: false,
+            sidebar_visible: false,
+            sidebar_focused: false,
+            folder_tree: None,
+            tree_expanded: HashSet::new(),
+            tree_selected: 0,
+            status_message: None,
+            settings: Settings::default(),
+            code_theme: crate::app::highlight::DEFAULT_THEME.to_string(),
+            needs_redraw: true,
+            last_draw: Instant::now(),
+            link_picker: None,
+            text_prompt: None,
+            confirmation: None,
+            saved_search_selected: None,
+            tag_browser: None,
+            link_graph: None,
+            quick_look: None,
+            preview_content: None,
+            preview_locked_path: None,
+            seeded_results: None,
+            link_inserter: None,
+            preview_loading: None,
+            preview_check_due: false,
+            benchmark_report: None,
+            send_to_selected: None,
+            config_preview: None,
+            last_esc: None,
+            result_list_title: None,
+            event_tx,
+            event_rx,
         }
     }
 }
 
 impl App {
     pub fn new() -> Self {
-        Self::default()
+        let mut app = Self::default();
+        match Settings::load() {
+            Ok(settings) => app.settings = settings,
+            Err(e) => app.set_status(e),
+        }
+        if let Some(path) = app.settings.log_file.clone() {
+            match crate::app::debug_log::open(&path) {
+                Ok(()) => crate::app::debug_log::log("Session started"),
+                Err(e) => app.set_status(e),
+            }
+        }
+        app.code_theme = app
+            .settings
+            .code_theme
+            .clone()
+            .or_else(crate::config_editor::config_theme)
+            .unwrap_or_else(|| crate::app::highlight::DEFAULT_THEME.to_string());
+        // A misspelt theme would otherwise fall back to the default without a word.
+        // The notemancy theme is shared with other frontends, so only code_theme warns
+        if !crate::app::highlight::theme_names().contains(&app.code_theme) {
+            if app.settings.code_theme.is_some() {
+                app.set_status(format!(
+                    "Unknown code_theme \"{}\"; using {} (t cycles the available themes)",
+                    app.code_theme,
+                    crate::app::highlight::DEFAULT_THEME
+                ));
+            }
+            app.code_theme = crate::app::highlight::DEFAULT_THEME.to_string();
+        }
+        app.resolve_accent();
+        if app.settings.track_seen {
+            app.seen_notes = crate::app::settings::load_seen_notes();
+        }
+        app.search_history = crate::app::settings::load_search_history();
+        app.indexed_times = crate::app::diff::load_indexed_times();
+        app
+    }
+
+    /// Starts on a result list of `paths` instead of scanning the vault.
+    pub fn seed_results(&mut self, paths: Vec<String>) {
+        let results = paths
+            .iter()
+            .map(|path| note_result(path, String::new()))
+            .collect();
+        self.seeded_results = Some(results);
     }
 
     pub fn set_search_engine(&mut self, engine: SearchEngine) {
         self.search_engine = Some(engine);
     }
 
+    /// Preview rendering as configured, with the code theme currently in use.
+    pub fn render_options(&self) -> crate::app::highlight::RenderOptions<'_> {
+        crate::app::highlight::RenderOptions {
+            html_mode: self.settings.preview_html,
+            theme: &self.code_theme,
+            join_soft_breaks: self.settings.join_soft_breaks,
+            code_width: None,
+        }
+    }
+
+    /// Shows a transient message in the status line.
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        crate::app::debug_log::log(format!("Status: {}", message));
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    /// Returns the status message if it is still recent enough to display.
+    pub fn current_status(&self) -> Option<&str> {
+        match self.status_message {
+            Some((ref message, set_at)) if set_at.elapsed() < Duration::from_secs(3) => {
+                Some(message.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// The vault directory configured in notemancy, or the common ancestor of the
+    /// scanned notes when the config names none.
+    pub fn vault_root(&self) -> std::path::PathBuf {
+        self.vault_root.clone()
+    }
+
+    /// Paths of every note found by the startup scan.
+    pub fn scanned_paths(&self) -> Vec<String> {
+        self.scan_result
+            .as_ref()
+            .map(|files| {
+                files
+                    .iter()
+                    .map(|file| {
+                        std::path::Path::new(&file.path)
+                            .to_string_lossy()
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Shows a command-produced list of notes in the search view, ready to navigate and open.
+    pub fn show_result_list(&mut self, title: impl Into<String>, results: Vec<SearchResult>) {
+        self.cancel_semantic_search();
+        self.search_query.clear();
+        self.search_results = results;
+        self.selected_search_index = 0;
+        self.preview_scroll = 0;
+        self.result_list_title = Some(title.into());
+        self.input_mode = InputMode::Normal;
+        self.state = AppState::Search;
+    }
+
+    /// Lists the notes in the same folder as `note`, with `note` selected.
+    pub fn show_folder_of(&mut self, note: &str) {
+        let folder = std::path::Path::new(note)
+            .parent()
+            .unwrap_or(std::path::Path::new(""));
+        let mut notes: Vec<String> = self
+            .scanned_paths()
+            .into_iter()
+            .filter(|path| std::path::Path::new(path).parent() == Some(folder))
+            .collect();
+        notes.sort_by_key(|path| path.to_lowercase());
+        let selected = notes.iter().position(|path| path == note).unwrap_or(0);
+        let title = folder
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+        let results = notes
+            .iter()
+            .map(|path| note_result(path, String::new()))
+            .collect();
+        self.show_result_list(title, results);
+        self.selected_search_index = selected;
+    }
+
+    /// Lists notes that no other note links to.
+    pub fn show_orphan_notes(&mut self) {
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        let counts = crate::app::links::incoming_link_counts(&paths);
+        let mut orphans: Vec<SearchResult> = paths
+            .iter()
+            .filter(|path| counts.get(*path).copied().unwrap_or(0) == 0)
+            .map(|path| note_result(path, "No incoming links".to_string()))
+            .collect();
+        orphans.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+        let title = format!("Orphan Notes ({})", orphans.len());
+        self.show_result_list(title, orphans);
+    }
+
+    /// Collects every scanned note's frontmatter tags on a worker thread, then
+    /// opens the tag browser.
+    pub fn open_tag_browser(&mut self) {
+        self.state = self.palette_return_state;
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        self.set_status("Reading tags...");
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::Status("Reading tags failed: thread terminated unexpectedly".to_string()),
+            );
+            let mut by_tag: HashMap<String, Vec<String>> = HashMap::new();
+            for path in paths {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                for tag in crate::app::frontmatter::tags(&content) {
+                    let notes = by_tag.entry(tag).or_default();
+                    // A tag listed twice in one note still counts the note once
+                    if notes.last() != Some(&path) {
+                        notes.push(path.clone());
+                    }
+                }
+            }
+            let mut tags: Vec<(String, Vec<String>)> = by_tag.into_iter().collect();
+            tags.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+            let _ = tx.send(AppEvent::TagIndex(tags));
+        });
+    }
+
+    fn handle_tag_browser_key(&mut self, key: KeyEvent) {
+        let Some(ref mut browser) = self.tag_browser else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.tag_browser = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                browser.selected = browser.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if browser.selected + 1 < browser.tags.len() {
+                    browser.selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                let (tag, notes) = browser.tags[browser.selected].clone();
+                self.tag_browser = None;
+                let mut results: Vec<SearchResult> = notes
+                    .iter()
+                    .map(|path| note_result(path, format!("#{}", tag)))
+                    .collect();
+                results.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+                self.show_result_list(format!("#{} ({})", tag, results.len()), results);
+            }
+            _ => {}
+        }
+    }
+
+    /// Lists the scanned notes that have never been previewed.
+    pub fn show_unread_notes(&mut self) {
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.state = self.palette_return_state;
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        if !self.settings.track_seen {
+            self.state = self.palette_return_state;
+            self.set_status("Seen notes aren't tracked; set track_seen to true");
+            return;
+        }
+        let mut unread: Vec<SearchResult> = paths
+            .iter()
+            .filter(|path| !self.seen_notes.contains(*path))
+            .map(|path| note_result(path, "Never previewed".to_string()))
+            .collect();
+        unread.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+        let title = format!("Unread ({} of {})", unread.len(), paths.len());
+        self.show_result_list(title, unread);
+    }
+
+    /// Lists notes whose file changed after `input` (see `parse_since`), most
+    /// recently modified first.
+    fn show_modified_since(&mut self, input: &str) {
+        let Some(cutoff) = parse_since(input) else {
+            self.set_status(format!(
+                "Can't read \"{}\": use YYYY-MM-DD or a span like 12h, 7d, 2w",
+                input
+            ));
+            return;
+        };
+        let mut modified: Vec<(std::time::SystemTime, String)> = self
+            .scanned_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let mtime = std::fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .ok()?;
+                (mtime > cutoff).then_some((mtime, path))
+            })
+            .collect();
+        modified.sort_by(|a, b| b.0.cmp(&a.0));
+        let results = modified
+            .into_iter()
+            .map(|(mtime, path)| {
+                let at = chrono::DateTime::<chrono::Local>::from(mtime);
+                note_result(&path, format!("Modified {}", at.format("%Y-%m-%d %H:%M")))
+            })
+            .collect();
+        self.show_result_list(format!("Modified since {}", input), results);
+    }
+
+    /// Deletes the console's snapshots of notes that no longer exist. The vector
+    /// index itself is left alone; notemancy_core can't delete single vectors, so
+    /// their embeddings go with the next Index Vectors.
+    pub fn forget_deleted_snapshots(&mut self) {
+        self.state = self.palette_return_state;
+        match crate::app::diff::prune_snapshots() {
+            Ok(0) => self.set_status("No snapshots of deleted notes"),
+            Ok(removed) => {
+                self.set_status(format!("Deleted {} snapshots of deleted notes", removed))
+            }
+            Err(e) => self.set_status(format!("Could not delete snapshots: {}", e)),
+        }
+    }
+
+    /// Checks every scanned note against both indexes on a worker thread; the
+    /// result arrives as `AppEvent::IndexHealth`. notemancy_core can't list what
+    /// either index holds, so each note is probed: it counts as full-text indexed
+    /// when a search for its file name returns it, and as embedded when it is among
+    /// its own nearest neighbours.
+    pub fn check_index_health(&mut self) {
+        self.state = self.palette_return_state;
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        if self.index_health_running {
+            self.set_status("Index health check already running");
+            return;
+        }
+        let Some(engine) = self.search_engine.clone() else {
+            self.set_status("Search engine not ready yet");
+            return;
+        };
+        self.index_health_running = true;
+        self.set_status(format!(
+            "Checking {} notes against both indexes...",
+            paths.len()
+        ));
+        let source = self.settings.related_source;
+        let prefix_chars = self.settings.related_prefix_chars;
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let failed = "Index health check terminated unexpectedly".to_string();
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::IndexHealth {
+                    total: paths.len(),
+                    missing_full_text: Err(failed.clone()),
+                    missing_vectors: Err(failed),
+                },
+            );
+            let missing_full_text = Ok(paths
+                .iter()
+                .filter(|path| !in_full_text_index(&engine, path))
+                .cloned()
+                .collect());
+
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let missing_vectors = rt.block_on(async {
+                let config = notemancy_core::config::load_config()
+                    .map_err(|e| format!("Error loading config: {}", e))?;
+                let ai = log_ai_init(
+                    "Index health check",
+                    notemancy_core::ai::AI::new(&config).await,
+                )
+                .map_err(|e| format!("Error initializing AI: {}", e))?;
+                let mut missing = Vec::new();
+                for path in &paths {
+                    let Ok(content) = std::fs::read_to_string(path) else {
+                        continue;
+                    };
+                    let title = note_result(path, String::new()).title;
+                    let text = source.text_for(&title, &content, prefix_chars);
+                    let embedded =
+                        ai.find_similar_documents(&text, 3, None)
+                            .await
+                            .is_ok_and(|similar| {
+                                similar.iter().any(|(doc, _)| {
+                                    doc.metadata
+                                        .get("physical_path")
+                                        .is_some_and(|indexed| same_note(indexed, path))
+                                })
+                            });
+                    if !embedded {
+                        missing.push(path.clone());
+                    }
+                }
+                Ok::<_, String>(missing)
+            });
+
+            let _ = tx.send(AppEvent::IndexHealth {
+                total: paths.len(),
+                missing_full_text,
+                missing_vectors,
+            });
+        });
+    }
+
+    /// Lists the notes the Index Health Check found missing from either index and
+    /// offers to rebuild the ones that lack notes.
+    fn show_index_health(
+        &mut self,
+        total: usize,
+        missing_full_text: Result<Vec<String>, String>,
+        missing_vectors: Result<Vec<String>, String>,
+    ) {
+        let full_text = match missing_full_text {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.set_status(format!("Index health check failed: {}", e));
+                return;
+            }
+        };
+        // Without the AI backend the full-text side is still worth reporting
+        let (vectors, vector_error) = match missing_vectors {
+            Ok(paths) => (paths, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        let mut notes: Vec<SearchResult> = Vec::new();
+        for path in full_text.iter().chain(&vectors) {
+            if notes.iter().any(|note| note.path == *path) {
+                continue;
+            }
+            let snippet = match (full_text.contains(path), vectors.contains(path)) {
+                (true, true) => "Missing from both indexes",
+                (true, false) => "Missing from the full-text index",
+                _ => "Missing from the vector index",
+            };
+            notes.push(note_result(path, snippet.to_string()));
+        }
+        let vector_count = match vector_error {
+            Some(ref e) => {
+                self.set_status(format!("Vector index not checked: {}", e));
+                "unchecked".to_string()
+            }
+            None => vectors.len().to_string(),
+        };
+        if notes.is_empty() {
+            if vector_error.is_none() {
+                self.set_status(format!("All {} scanned notes are in both indexes", total));
+            }
+            return;
+        }
+        notes.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+        let title = format!(
+            "Index Health ({} missing from full-text, {} from vectors, of {})",
+            full_text.len(),
+            vector_count,
+            total
+        );
+        self.show_result_list(title, notes);
+        let (full_text, vectors) = (!full_text.is_empty(), !vectors.is_empty());
+        let prompt = match (full_text, vectors) {
+            (true, true) => "Rebuild the full-text index, then re-embed every note?",
+            (true, false) => "Rebuild the full-text index?",
+            _ => "Re-embed every note into the vector index?",
+        };
+        self.confirm(
+            prompt,
+            ConfirmAction::ReconcileIndexes { full_text, vectors },
+        );
+    }
+
+    /// Rebuilds whichever indexes the Index Health Check found incomplete. Vector
+    /// indexing waits for a full-text rebuild, as it does after an edit.
+    fn reconcile_indexes(&mut self, full_text: bool, vectors: bool) {
+        if full_text {
+            self.reembed_after_index = vectors;
+            self.indexing_return_state = Some(self.state);
+            self.start_indexing();
+        } else if vectors {
+            self.enter_vector_indexing_mode();
+        }
+    }
+
+    /// Diffs the selected note against its snapshot from the last vector index
+    /// and shows the result in the preview pane.
+    pub fn show_index_diff(&mut self) {
+        self.state = self.palette_return_state;
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            self.set_status("Select a note in the search view first");
+            return;
+        };
+        let path = result.path.clone();
+        let Some(indexed) = crate::app::diff::load_snapshot(&path) else {
+            self.set_status("No snapshot of this note; run Index Vectors first");
+            return;
+        };
+        let current = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_status(format!("Could not read note: {}", e));
+                return;
+            }
+        };
+        let diff = crate::app::diff::diff_lines(&indexed, &current);
+        if diff
+            .iter()
+            .all(|line| matches!(line, crate::app::diff::DiffLine::Same(_)))
+        {
+            self.set_status("No changes since the last index");
+            return;
+        }
+        self.detail_view_mode = DetailViewMode::Preview;
+        self.preview_scroll = 0;
+        self.preview_diff = Some((path, diff));
+    }
+
+    /// The state to land in once an external program (editor, shell command) returns.
+    /// Search sessions survive the round-trip when `keep_search_on_return` is set.
+    pub fn state_after_external(&self, previous: AppState) -> AppState {
+        if self.settings.keep_search_on_return && previous == AppState::Search {
+            AppState::Search
+        } else {
+            AppState::Preview
+        }
+    }
+
+    /// True while something on screen animates (spinners) or a status message is pending expiry.
+    fn is_animating(&self) -> bool {
+        matches!(
+            self.state,
+            AppState::Starting
+                | AppState::Scanning
+                | AppState::Indexing
+                | AppState::IndexingVectors
+        ) || self.is_loading_related_files
+            || self.is_semantic_searching
+            || self.status_message.is_some()
+    }
+
+    /// Copies a `path.md#heading` link for the section at the current preview scroll position.
+    fn yank_permalink(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let path = result.path.clone();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_status(format!("Could not read note: {}", e));
+                return;
+            }
+        };
+
+        // The enclosing section is the last heading at or above the top of the viewport.
+        // The scroll offset counts wrapped rows, so compare against the drawn heading rows.
+        let rendered = crate::app::highlight::render_markdown(&content, self.render_options());
+        let scroll = self.preview_scroll as usize;
+        let enclosing = rendered
+            .headings
+            .iter()
+            .zip(&self.preview_layout.heading_rows)
+            .rev()
+            .find(|(_, &row)| row <= scroll)
+            .map(|(heading, _)| heading);
+        let permalink = match enclosing {
+            Some(heading) => format!(
+                "{}#{}",
+                path,
+                crate::app::highlight::heading_slug(&heading.title)
+            ),
+            None => path,
+        };
+
+        match crate::app::clipboard::copy_to_clipboard(&permalink) {
+            Ok(()) => self.set_status(format!("Copied {}", permalink)),
+            Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+        }
+    }
+
+    /// Copies the related files on screen as a markdown list, ready to paste as a "See also" section.
+    fn yank_related_links(&mut self) {
+        if self.related_files.is_empty() {
+            self.set_status("No related files to copy".to_string());
+            return;
+        }
+        let list = self
+            .related_files
+            .iter()
+            .map(|result| {
+                let title = if result.title.is_empty() {
+                    std::path::Path::new(&result.path)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_else(|| result.path.clone())
+                } else {
+                    result.title.clone()
+                };
+                format!(
+                    "- {}",
+                    crate::app::clipboard::markdown_link(&title, &result.path)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match crate::app::clipboard::copy_to_clipboard(&list) {
+            Ok(()) => self.set_status(format!("Copied {} links", self.related_files.len())),
+            Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+        }
+    }
+
+    /// The current results as CSV or JSON, labelled with the query or list title.
+    fn exported_results(&self, format: crate::app::export::ExportFormat) -> String {
+        let query = self
+            .result_list_title
+            .as_deref()
+            .unwrap_or(&self.search_query);
+        crate::app::export::export_results(query, &self.search_results, format)
+    }
+
+    pub fn copy_results(&mut self, format: crate::app::export::ExportFormat) {
+        self.state = self.palette_return_state;
+        if self.search_results.is_empty() {
+            self.set_status("No results to export");
+            return;
+        }
+        let exported = self.exported_results(format);
+        match crate::app::clipboard::copy_to_clipboard(&exported) {
+            Ok(()) => self.set_status(format!(
+                "Copied {} results as {}",
+                self.search_results.len(),
+                format.label()
+            )),
+            Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+        }
+    }
+
+    /// Asks where to write the current results, then writes them there.
+    pub fn prompt_save_results(&mut self, format: crate::app::export::ExportFormat) {
+        self.state = self.palette_return_state;
+        if self.search_results.is_empty() {
+            self.set_status("No results to export");
+            return;
+        }
+        self.open_text_prompt(
+            format!("Save {} to", format.label()),
+            PromptAction::ExportResults(format),
+        );
+    }
+
+    fn save_results(&mut self, format: crate::app::export::ExportFormat, path: &str) {
+        let path = match path.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None => std::path::PathBuf::from(path),
+        };
+        match std::fs::write(&path, self.exported_results(format)) {
+            Ok(()) => self.set_status(format!(
+                "Saved {} results to {}",
+                self.search_results.len(),
+                path.display()
+            )),
+            Err(e) => self.set_status(format!("Could not write {}: {}", path.display(), e)),
+        }
+    }
+
     pub fn enter_vector_indexing_mode(&mut self) {
         self.state = AppState::IndexingVectors;
         self.vector_indexing_status = Some("Starting vector indexing...".to_string());
@@ -138,42 +1226,78 @@ impl App {
         use notemancy_core::ai::AI;
         use notemancy_core::config::load_config;
 
-        // Create a channel to communicate status updates
-        let (tx, rx) = std::sync::mpsc::channel::<String>();
-        self.vector_indexing_receiver = Some(rx);
+        // Status updates go back to the main loop over the shared channel
+        let tx = self.event_tx.clone();
+        let attempts = self.settings.ai_max_attempts;
+        // Snapshot what gets indexed so later edits can be diffed against it
+        let note_paths = self.scanned_paths();
 
         // Create a thread to handle the indexing
         std::thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::VectorIndexStatus(
+                    "Error: vector indexing thread terminated unexpectedly".to_string(),
+                ),
+            );
             // Initialize runtime for async operations
             let rt = tokio::runtime::Runtime::new().unwrap();
 
             // Run the indexing process
             rt.block_on(async {
+                let on_retry = |attempt: u32, max: u32| {
+                    let _ = tx.send(AppEvent::VectorIndexStatus(format!(
+                        "Retrying ({}/{})...",
+                        attempt, max
+                    )));
+                };
                 // First load the configuration
                 match load_config() {
                     Ok(config) => {
                         // Create the AI instance with config
-                        match AI::new(&config).await {
+                        let ai = with_retry(attempts, &on_retry, || AI::new(&config)).await;
+                        match log_ai_init("Vector indexing", ai) {
                             Ok(ai) => {
-                                let _ = tx.send("Processing documents...".to_string());
+                                let _ = tx.send(AppEvent::VectorIndexStatus(
+                                    "Processing documents...".to_string(),
+                                ));
 
                                 // Use the correct module name: vec_indexer
                                 match notemancy_core::vec_indexer::index_markdown_files(&ai).await {
                                     Ok(_) => {
-                                        let _ = tx.send("SUCCESS".to_string());
+                                        if let Err(e) =
+                                            crate::app::diff::save_snapshots(&note_paths)
+                                        {
+                                            let _ = tx.send(AppEvent::Status(format!(
+                                                "Could not save note snapshots: {}",
+                                                e
+                                            )));
+                                        }
+                                        let _ = tx.send(AppEvent::VectorIndexStatus(
+                                            "SUCCESS".to_string(),
+                                        ));
                                     }
                                     Err(e) => {
-                                        let _ = tx.send(format!("Error: {}", e));
+                                        let _ = tx.send(AppEvent::VectorIndexStatus(format!(
+                                            "Error: {}",
+                                            e
+                                        )));
                                     }
                                 }
                             }
                             Err(e) => {
-                                let _ = tx.send(format!("Error initializing AI: {}", e));
+                                let _ = tx.send(AppEvent::VectorIndexStatus(format!(
+                                    "Error initializing AI: {}",
+                                    e
+                                )));
                             }
                         }
                     }
                     Err(e) => {
-                        let _ = tx.send(format!("Error loading config: {}", e));
+                        let _ = tx.send(AppEvent::VectorIndexStatus(format!(
+                            "Error loading config: {}",
+                            e
+                        )));
                     }
                 }
             });
@@ -189,65 +1313,299 @@ impl App {
                     app.enter_search_mode(terminal);
                     app.state = AppState::Search;
                 }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Saved Searches",
+                description: "Run a query saved with F in the search view",
+                action: Box::new(|app, _terminal| {
+                    app.state = app.palette_return_state;
+                    app.open_saved_searches();
+                }),
+                children: Vec::new(),
             },
+            crate::app::command_palette::CommandItem::category(
+                "Indexing",
+                "Rebuild the full-text or vector index",
+                vec![
+                    crate::app::command_palette::CommandItem {
+                        name: "Index Vectors",
+                        description: "Generate vector embeddings for all markdown files",
+                        action: Box::new(|app, _terminal| {
+                            app.state = app.palette_return_state;
+                            app.confirm(
+                                "Re-embed every note? This calls the AI backend for the whole vault.",
+                                ConfirmAction::IndexVectors,
+                            );
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Rebuild Search Index",
+                        description: "Re-index every document for full-text search",
+                        action: Box::new(|app, _terminal| {
+                            app.state = app.palette_return_state;
+                            app.confirm(
+                                "Rebuild the full-text index from scratch?",
+                                ConfirmAction::RebuildSearchIndex,
+                            );
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Benchmark",
+                        description:
+                            "Time scanning, indexing, searching and a related-files lookup",
+                        action: Box::new(|app, _terminal| {
+                            app.start_benchmark();
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Forget Deleted Note Snapshots",
+                        description: "Delete the console's snapshots of notes that no longer exist",
+                        action: Box::new(|app, _terminal| {
+                            app.state = app.palette_return_state;
+                            app.confirm(
+                                "Delete the snapshots of deleted notes? The vector index is not changed.",
+                                ConfirmAction::ForgetDeletedSnapshots,
+                            );
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Index Health Check",
+                        description:
+                            "Find scanned notes missing from the full-text or vector index",
+                        action: Box::new(|app, _terminal| {
+                            app.check_index_health();
+                        }),
+                        children: Vec::new(),
+                    },
+                ],
+            ),
             crate::app::command_palette::CommandItem {
-                name: "Index Vectors",
-                description: "Generate vector embeddings for all markdown files",
+                name: "Diff Since Last Index",
+                description: "Show what changed in the selected note since vector indexing",
                 action: Box::new(|app, _terminal| {
-                    app.enter_vector_indexing_mode();
+                    app.show_index_diff();
                 }),
+                children: Vec::new(),
             },
             crate::app::command_palette::CommandItem {
-                name: "Open Config Editor",
-                description: "Edit configuration file",
-                action: Box::new(|app, terminal| {
-                    if let Err(e) = crate::config_editor::open_config_in_editor(terminal) {
-                        eprintln!("Error opening config: {}", e);
+                name: "Append to Daily Note",
+                description: "Add a timestamped line to today's daily note",
+                action: Box::new(|app, _terminal| {
+                    app.state = app.palette_return_state;
+                    if app.refuse_if_read_only() {
+                        return;
                     }
-                    app.state = AppState::Preview;
+                    app.open_text_prompt("Append to Daily Note", PromptAction::AppendToDailyNote);
                 }),
+                children: Vec::new(),
             },
             crate::app::command_palette::CommandItem {
-                name: "Quit",
-                description: "Exit the application",
+                name: "Start Writing",
+                description: "Open a new or existing note full screen in the editor",
                 action: Box::new(|app, _terminal| {
-                    app.quit();
+                    app.state = app.palette_return_state;
+                    if app.refuse_if_read_only() {
+                        return;
+                    }
+                    app.open_text_prompt(
+                        "Write in (path relative to the vault)",
+                        PromptAction::StartWriting,
+                    );
                 }),
+                children: Vec::new(),
             },
-        ];
-        self.selected_command_index = 0;
-        self.state = AppState::CommandPalette;
-    }
-
-    /// Run the application.
-    pub fn run(
-        mut self,
-        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
-    ) -> Result<()> {
-        enable_raw_mode()?;
-        println!("notemancy is starting");
-
-        let (tx, rx) = mpsc::channel::<Result<(Vec<ScannedFile>, String), Report>>();
-        self.scanning_receiver = Some(rx);
-        self.state = AppState::Scanning;
-
-        thread::spawn(move || {
-            let scanner = match Scanner::from_config().map_err(|e| Report::msg(e.to_string())) {
-                Ok(s) => s,
-                Err(e) => return tx.send(Err(e)).unwrap_or(()),
-            };
-            let res = scanner
-                .scan_markdown_files()
-                .map_err(|e| Report::msg(e.to_string()));
-            tx.send(res).unwrap_or(());
+            crate::app::command_palette::CommandItem {
+                name: "Go to Path",
+                description: "Select a note by its path, relative to the vault or absolute",
+                action: Box::new(|app, _terminal| {
+                    app.state = app.palette_return_state;
+                    app.open_text_prompt("Go to Path", PromptAction::GoToPath);
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Link Graph",
+                description: "Show what the selected note links to and what links to it",
+                action: Box::new(|app, _terminal| {
+                    app.state = app.palette_return_state;
+                    app.open_link_graph();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem::category(
+                "Export Results",
+                "Copy or save the current results with their query",
+                vec![
+                    crate::app::command_palette::CommandItem {
+                        name: "Copy as CSV",
+                        description: "Copy paths, titles and scores to the clipboard as CSV",
+                        action: Box::new(|app, _terminal| {
+                            app.copy_results(crate::app::export::ExportFormat::Csv);
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Copy as JSON",
+                        description: "Copy paths, titles and scores to the clipboard as JSON",
+                        action: Box::new(|app, _terminal| {
+                            app.copy_results(crate::app::export::ExportFormat::Json);
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Save as CSV",
+                        description: "Write the results to a CSV file",
+                        action: Box::new(|app, _terminal| {
+                            app.prompt_save_results(crate::app::export::ExportFormat::Csv);
+                        }),
+                        children: Vec::new(),
+                    },
+                    crate::app::command_palette::CommandItem {
+                        name: "Save as JSON",
+                        description: "Write the results to a JSON file",
+                        action: Box::new(|app, _terminal| {
+                            app.prompt_save_results(crate::app::export::ExportFormat::Json);
+                        }),
+                        children: Vec::new(),
+                    },
+                ],
+            ),
+            crate::app::command_palette::CommandItem {
+                name: "Show Unread",
+                description: "List notes that have never been previewed",
+                action: Box::new(|app, _terminal| {
+                    app.show_unread_notes();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Modified Since",
+                description: "List notes changed after a date (2024-05-01) or within a span (7d)",
+                action: Box::new(|app, _terminal| {
+                    app.state = app.palette_return_state;
+                    app.open_text_prompt(
+                        "Modified since (YYYY-MM-DD, or e.g. 12h, 7d, 2w)",
+                        PromptAction::ModifiedSince,
+                    );
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Browse Tags",
+                description: "Pick a frontmatter tag, previewing its notes, and list them",
+                action: Box::new(|app, _terminal| {
+                    app.open_tag_browser();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Orphan Notes",
+                description: "List notes that no other note links to",
+                action: Box::new(|app, _terminal| {
+                    app.show_orphan_notes();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "View Config",
+                description: "Show the configuration file without editing it",
+                action: Box::new(|app, _terminal| {
+                    app.open_config_preview();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Show Effective Settings",
+                description: "Show the settings in effect, defaults filled in",
+                action: Box::new(|app, _terminal| {
+                    app.open_resolved_settings();
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Open Config Editor",
+                description: "Edit configuration file",
+                action: Box::new(|app, terminal| {
+                    if let Err(e) = crate::config_editor::open_config_in_editor(terminal) {
+                        eprintln!("Error opening config: {}", e);
+                    }
+                    app.state = app.state_after_external(app.palette_return_state);
+                }),
+                children: Vec::new(),
+            },
+            crate::app::command_palette::CommandItem {
+                name: "Quit",
+                description: "Exit the application",
+                action: Box::new(|app, _terminal| {
+                    app.quit();
+                }),
+                children: Vec::new(),
+            },
+        ];
+        self.selected_command_index = 0;
+        self.palette_filter.clear();
+        self.palette_stack.clear();
+        self.palette_return_state = self.state;
+        self.palette_search_active = false;
+        self.palette_search_query.clear();
+        self.palette_search_results.clear();
+        self.palette_search_selected = 0;
+        self.state = AppState::CommandPalette;
+    }
+
+    /// Run the application.
+    pub fn run(
+        mut self,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) -> Result<()> {
+        enable_raw_mode()?;
+
+        // Opening the search index can take a while on big vaults, so it happens on a
+        // worker thread behind the starting screen; the scan follows once it is done
+        self.state = AppState::Starting;
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::SearchEngineReady(Err("thread terminated unexpectedly".to_string())),
+            );
+            let engine = notemancy_core::search::init_search_engine().map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::SearchEngineReady(engine));
         });
 
         self.running = true;
         while self.running {
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
+            // Wake up in time for the next spinner frame even when no input arrives
+            let tick_interval = Duration::from_millis(self.settings.spinner_interval_ms.max(1));
+            let poll_timeout = tick_interval
+                .saturating_sub(self.last_tick.elapsed())
+                .min(Duration::from_millis(self.settings.poll_interval_ms));
+            let mut resized = false;
+            if event::poll(poll_timeout)? {
+                let event = event::read()?;
+                // Any input (keys, resizes, ...) may change what is on screen
+                self.needs_redraw = true;
+                // Layout, wrapping and truncation all follow the frame size, so a
+                // resize is drawn right away instead of waiting out the throttle
+                resized = matches!(event, Event::Resize(..));
+                if let Event::Key(key) = event {
                     if key.kind == KeyEventKind::Press {
-                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                        if self.benchmark_report.is_some() {
+                            self.benchmark_report = None;
+                        } else if self.confirmation.is_some() {
+                            self.handle_confirmation_key(key);
+                        } else if self.text_prompt.is_some() {
+                            self.handle_text_prompt_key(key, terminal);
+                        } else if self.saved_search_selected.is_some() {
+                            self.handle_saved_search_key(key);
+                        } else if self.tag_browser.is_some() {
+                            self.handle_tag_browser_key(key);
+                        } else if key.modifiers.contains(KeyModifiers::CONTROL)
                             && key.code == KeyCode::Char('s')
                         {
                             self.enter_search_mode(terminal);
@@ -263,80 +1621,560 @@ impl App {
                         }
                     }
                 }
-            } else if self.last_tick.elapsed() >= Duration::from_millis(100) {
+            }
+            if self.last_tick.elapsed() >= tick_interval {
                 self.spinner_idx = (self.spinner_idx + 1) % self.spinner_chars.len();
                 self.last_tick = Instant::now();
+                self.preview_check_due = true;
+
+                // Clear an expired status message so the help line comes back
+                if self.status_message.is_some() && self.current_status().is_none() {
+                    self.status_message = None;
+                    self.needs_redraw = true;
+                }
+                if self.is_animating() {
+                    self.needs_redraw = true;
+                }
             }
 
-            if self.state == AppState::IndexingVectors {
-                if let Some(ref rx) = self.vector_indexing_receiver {
-                    match rx.try_recv() {
-                        Ok(status) => {
-                            if status == "SUCCESS" {
-                                self.vector_indexing_status =
-                                    Some("Vector indexing completed successfully!".to_string());
-                                self.vector_indexing_complete = true;
-                                self.vector_indexing_success_time = Some(Instant::now());
-                            } else if status.starts_with("Error") {
-                                self.vector_indexing_status = Some(status);
-                                self.vector_indexing_complete = true;
-                                self.vector_indexing_success_time = Some(Instant::now());
-                            } else {
-                                self.vector_indexing_status = Some(status);
-                            }
-                        }
-                        Err(TryRecvError::Empty) => {}
-                        Err(TryRecvError::Disconnected) => {
-                            self.vector_indexing_receiver = None;
-                            self.state = AppState::Preview;
-                        }
-                    }
+            // A scan thread sends its result before exiting, so if it has exited by
+            // now the result is already queued and handled below
+            let scan_exited = self
+                .scan_thread
+                .as_ref()
+                .is_some_and(|handle| handle.is_finished());
+
+            // Handle everything the background workers have sent since the last pass
+            while let Ok(event) = self.event_rx.try_recv() {
+                self.needs_redraw = true;
+                self.handle_event(event);
+            }
+
+            // The thread exited without reporting, e.g. the scanner panicked
+            if scan_exited && self.scan_thread.take().is_some() {
+                self.set_status("Scan failed — thread terminated unexpectedly");
+                if self.state == AppState::Scanning {
+                    self.state = AppState::Preview;
                 }
+                self.needs_redraw = true;
+            }
 
-                // Check if we need to return to the Preview state after showing success
+            // Check if we need to return to the Preview state after showing success
+            if self.state == AppState::IndexingVectors {
                 if let Some(success_time) = self.vector_indexing_success_time {
                     if success_time.elapsed() >= Duration::from_secs(2) {
+                        self.needs_redraw = true;
                         self.state = AppState::Preview;
                         self.vector_indexing_success_time = None;
                     }
                 }
             }
 
-            if let Some(ref rx) = self.scanning_receiver {
-                match rx.try_recv() {
-                    Ok(result) => {
-                        match result {
-                            Ok((scanned_files, summary)) => {
-                                self.scan_result = Some(scanned_files);
-                                self.scan_summary = Some(summary);
-                            }
-                            Err(e) => eprintln!("Scanning error: {}", e),
-                        }
-                        self.state = AppState::Preview;
-                        self.scanning_receiver = None;
-                    }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Disconnected) => {
-                        self.scanning_receiver = None;
+            if self.state == AppState::Search {
+                self.record_navigation();
+            }
+            self.process();
+
+            let min_redraw_interval = Duration::from_millis(self.settings.min_redraw_interval_ms);
+            if self.needs_redraw && (resized || self.last_draw.elapsed() >= min_redraw_interval) {
+                if resized {
+                    // Repaint everything; cells left over from the old size would otherwise linger
+                    terminal.autoresize()?;
+                    terminal.clear()?;
+                }
+                terminal.draw(|frame| self.draw(frame))?;
+                self.needs_redraw = false;
+                self.last_draw = Instant::now();
+            }
+        }
+
+        disable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Scans the vault on a worker thread; the result arrives as `AppEvent::ScanFinished`.
+    fn start_scan(&mut self) {
+        crate::app::debug_log::log("Scan started");
+        let tx = self.event_tx.clone();
+        self.scan_started = Instant::now();
+
+        self.scan_thread = Some(thread::spawn(move || {
+            let scanner = match Scanner::from_config().map_err(|e| Report::msg(e.to_string())) {
+                Ok(s) => s,
+                Err(e) => return tx.send(AppEvent::ScanFinished(Err(e))).unwrap_or(()),
+            };
+            let res = scanner
+                .scan_markdown_files()
+                .map_err(|e| Report::msg(e.to_string()));
+            tx.send(AppEvent::ScanFinished(res)).unwrap_or(());
+        }));
+    }
+
+    /// Opens a one-line text prompt over the current screen.
+    pub fn open_text_prompt(&mut self, title: impl Into<String>, action: PromptAction) {
+        self.text_prompt = Some(TextPrompt {
+            title: title.into(),
+            input: String::new(),
+            action,
+        });
+    }
+
+    /// Runs `action` once confirmed, or right away when `confirm_style` is `none`.
+    pub fn confirm(&mut self, prompt: impl Into<String>, action: ConfirmAction) {
+        if self.settings.confirm_style == crate::app::settings::ConfirmStyle::None {
+            self.run_confirmed(action);
+            return;
+        }
+        self.confirmation = Some(Confirmation {
+            prompt: prompt.into(),
+            action,
+            input: String::new(),
+        });
+    }
+
+    fn handle_confirmation_key(&mut self, key: KeyEvent) {
+        let Some(ref mut confirmation) = self.confirmation else {
+            return;
+        };
+        let action = confirmation.action;
+        match self.settings.confirm_style {
+            crate::app::settings::ConfirmStyle::TypeYes => match key.code {
+                KeyCode::Esc => self.confirmation = None,
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    confirmation.input.push(c);
+                }
+                KeyCode::Backspace => {
+                    confirmation.input.pop();
+                }
+                KeyCode::Enter => {
+                    let confirmed = confirmation.input.trim().eq_ignore_ascii_case("yes");
+                    self.confirmation = None;
+                    if confirmed {
+                        self.run_confirmed(action);
+                    } else {
+                        self.set_status("Cancelled");
                     }
                 }
+                _ => {}
+            },
+            _ => {
+                self.confirmation = None;
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    self.run_confirmed(action);
+                } else {
+                    self.set_status("Cancelled");
+                }
+            }
+        }
+    }
+
+    /// Opens the saved searches picker.
+    pub fn open_saved_searches(&mut self) {
+        if self.settings.saved_searches.is_empty() {
+            self.set_status("No saved searches yet (F in the search view saves one)");
+            return;
+        }
+        self.saved_search_selected = Some(0);
+    }
+
+    fn handle_saved_search_key(&mut self, key: KeyEvent) {
+        let Some(selected) = self.saved_search_selected else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.saved_search_selected = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.saved_search_selected = Some(selected.saturating_sub(1));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if selected + 1 < self.settings.saved_searches.len() {
+                    self.saved_search_selected = Some(selected + 1);
+                }
+            }
+            KeyCode::Enter => {
+                self.saved_search_selected = None;
+                let query = self.settings.saved_searches[selected].query.clone();
+                self.input_mode = InputMode::Normal;
+                self.start_search(query);
+            }
+            _ => {}
+        }
+    }
+
+    /// Saves the current query under `name`, replacing a saved search of that name.
+    fn save_search(&mut self, name: &str) {
+        let search = crate::app::settings::SavedSearch {
+            name: name.to_string(),
+            query: self.search_query.clone(),
+        };
+        let saved = &mut self.settings.saved_searches;
+        match saved.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => *existing = search,
+            None => saved.push(search),
+        }
+        match self.settings.save() {
+            Ok(()) => self.set_status(format!("Saved search \"{}\"", name)),
+            Err(e) => self.set_status(format!("Could not save settings: {}", e)),
+        }
+    }
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::RebuildSearchIndex => self.rebuild_search_index(),
+            ConfirmAction::IndexVectors => self.enter_vector_indexing_mode(),
+            ConfirmAction::ForgetDeletedSnapshots => self.forget_deleted_snapshots(),
+            ConfirmAction::RescanVault => self.start_scan(),
+            ConfirmAction::ReconcileIndexes { full_text, vectors } => {
+                self.reconcile_indexes(full_text, vectors)
             }
+        }
+    }
 
-            if let Some(ref rx) = self.indexing_receiver {
-                if rx.try_recv().is_ok() {
-                    self.state = AppState::Search;
-                    self.indexing_receiver = None;
+    fn handle_text_prompt_key(
+        &mut self,
+        key: KeyEvent,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) {
+        let Some(ref mut prompt) = self.text_prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.text_prompt = None,
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.input.push(c);
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Enter => {
+                let input = prompt.input.trim().to_string();
+                let action = prompt.action;
+                self.text_prompt = None;
+                if input.is_empty() {
+                    return;
+                }
+                match action {
+                    PromptAction::AppendToDailyNote => self.append_to_daily_note(&input),
+                    PromptAction::ExportResults(format) => self.save_results(format, &input),
+                    PromptAction::GoToPath => self.go_to_path(&input),
+                    PromptAction::SaveSearch => self.save_search(&input),
+                    PromptAction::ModifiedSince => self.show_modified_since(&input),
+                    PromptAction::StartWriting => self.start_writing(&input, terminal),
                 }
             }
+            _ => {}
+        }
+    }
 
-            // self.process_related_files_receiver();
-            self.process();
+    /// Whether `read_only` blocks a vault change, telling the user so when it does.
+    pub fn refuse_if_read_only(&mut self) -> bool {
+        if self.settings.read_only {
+            self.set_status("Vault is read-only");
+        }
+        self.settings.read_only
+    }
+
+    /// Appends `text` to today's daily note, then rescans so the note is searchable.
+    fn append_to_daily_note(&mut self, text: &str) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let now = chrono::Local::now();
+        let root = self.vault_root();
+        let path =
+            crate::app::journal::daily_note_path(&self.settings.daily_note_path, &root, &now);
+        match crate::app::journal::append_entry(&path, text, &now) {
+            Ok(()) => {
+                self.set_status(format!("Appended to {}", path.display()));
+                self.start_scan();
+            }
+            Err(e) => self.set_status(e),
+        }
+    }
 
-            terminal.draw(|frame| self.draw(frame))?;
+    /// Turns `accent_color` into the color the UI draws with.
+    fn resolve_accent(&mut self) {
+        let Some(setting) = self.settings.accent_color.clone() else {
+            return;
+        };
+        let root = self.vault_root();
+        self.accent = crate::app::ui::accent_color(&setting, &root);
+        if self.accent.is_none() && setting != "vault" {
+            self.set_status(format!(
+                "Invalid accent_color {}: expected #rrggbb or vault",
+                setting
+            ));
         }
+    }
 
-        disable_raw_mode()?;
-        Ok(())
+    /// Applies a message from a background worker to the app state.
+    fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::SearchEngineReady(engine) => {
+                match engine {
+                    Ok(engine) => {
+                        crate::app::debug_log::log("Search engine ready");
+                        self.set_search_engine(engine);
+                    }
+                    // Browsing and the palette still work without full-text search
+                    Err(e) => self.set_status(format!("Failed to initialize search engine: {}", e)),
+                }
+                if let Some(results) = self.seeded_results.take() {
+                    let title = format!("Input ({})", results.len());
+                    self.show_result_list(title, results);
+                    return;
+                }
+                // The palette and search are reachable while the engine opens; a user
+                // who has moved on stays put and the scan runs behind them
+                if self.state == AppState::Starting {
+                    self.state = AppState::Scanning;
+                }
+                self.start_scan();
+            }
+            AppEvent::ScanFinished(result) => {
+                self.scan_thread = None;
+                let first_scan = self.scan_result.is_none();
+                match result {
+                    Ok((mut scanned_files, summary)) => {
+                        let paths: Vec<String> = scanned_files
+                            .iter()
+                            .map(|file| std::path::Path::new(&file.path).display().to_string())
+                            .collect();
+                        self.vault_root = crate::config_editor::vault_root(&paths)
+                            .unwrap_or_else(|| crate::app::tree::common_ancestor(&paths));
+                        // `Scanner::from_config` has no hidden-file option, so filter here
+                        let mut hidden = String::new();
+                        if !self.settings.include_hidden {
+                            let before = scanned_files.len();
+                            let root = self.vault_root.clone();
+                            scanned_files
+                                .retain(|file| !is_hidden(&root, std::path::Path::new(&file.path)));
+                            hidden = format!(", {} hidden skipped", before - scanned_files.len());
+                        }
+                        self.set_status(format!(
+                            "Scanned {} notes in {:.1?}{}",
+                            scanned_files.len(),
+                            self.scan_started.elapsed(),
+                            hidden
+                        ));
+                        self.scan_result = Some(scanned_files);
+                        self.scan_summary = Some(summary);
+                        // Notes may have changed on disk; priorities and tags are read again
+                        // when needed
+                        self.priority_cache.clear();
+                        self.tag_cache.clear();
+                        self.resolve_accent();
+                    }
+                    Err(e) => self.set_status(format!("Scanning error: {}", e)),
+                }
+                if self.state == AppState::Scanning {
+                    self.state = AppState::Preview;
+                    self.run_startup_action();
+                } else {
+                    // A rescan: refresh the folder tree and the search index in place.
+                    // A startup scan the user didn't wait for leaves the index alone;
+                    // entering search indexes anyway.
+                    if self.folder_tree.is_some() {
+                        let paths = self.scanned_paths();
+                        self.folder_tree = Some(crate::app::tree::FolderTree::build(&paths));
+                    }
+                    if !first_scan {
+                        self.indexing_return_state = Some(self.state);
+                        self.start_indexing();
+                    }
+                }
+            }
+            AppEvent::IndexingFinished(error) => {
+                match error {
+                    Some(ref e) => crate::app::debug_log::log(format!("Indexing failed: {}", e)),
+                    None => crate::app::debug_log::log("Indexing finished"),
+                }
+                if self.state == AppState::Indexing {
+                    let rebuilding = self.indexing_return_state.is_some();
+                    self.state = self
+                        .indexing_return_state
+                        .take()
+                        .unwrap_or(AppState::Search);
+                    match error {
+                        Some(e) => self.set_status(format!("Indexing failed: {}", e)),
+                        None if rebuilding => self.set_status("Search index rebuilt"),
+                        None => {}
+                    }
+                    // A query restored at startup (or kept across a rebuild) runs on the fresh index
+                    if self.state == AppState::Search && !self.search_query.is_empty() {
+                        self.perform_search();
+                    }
+                    if std::mem::take(&mut self.reembed_after_index) {
+                        self.enter_vector_indexing_mode();
+                    }
+                }
+            }
+            AppEvent::VectorIndexStatus(status) => {
+                crate::app::debug_log::log(format!("Vector indexing: {}", status));
+                if status == "SUCCESS" {
+                    // Fresh embeddings make every cached lookup stale
+                    self.related_cache.clear();
+                    self.indexed_times = crate::app::diff::load_indexed_times();
+                    self.vector_indexing_status =
+                        Some("Vector indexing completed successfully!".to_string());
+                    self.vector_indexing_complete = true;
+                    self.vector_indexing_success_time = Some(Instant::now());
+                } else if status.starts_with("Error") {
+                    self.vector_indexing_status = Some(status);
+                    self.vector_indexing_complete = true;
+                    self.vector_indexing_success_time = Some(Instant::now());
+                } else {
+                    self.vector_indexing_status = Some(status);
+                }
+            }
+            AppEvent::Status(message) => self.set_status(message),
+            AppEvent::PreviewContent {
+                path,
+                modified,
+                content,
+            } => {
+                if self.preview_loading.as_deref() == Some(path.as_str()) {
+                    self.preview_loading = None;
+                }
+                // A reload of the same note keeps its place; a different note opens at its top
+                if self.preview_content.as_ref().map(|preview| &preview.path) != Some(&path) {
+                    self.preview_scroll = 0;
+                }
+                // The note changed since its priority or tags were read
+                if self
+                    .priority_cache
+                    .get(&path)
+                    .is_some_and(|(cached_at, _)| *cached_at != modified)
+                {
+                    self.priority_cache.remove(&path);
+                }
+                if self
+                    .tag_cache
+                    .get(&path)
+                    .is_some_and(|(cached_at, _)| *cached_at != modified)
+                {
+                    self.tag_cache.remove(&path);
+                }
+                self.preview_content = Some(PreviewContent {
+                    path,
+                    modified,
+                    content,
+                    rendered: None,
+                });
+            }
+            AppEvent::BenchmarkFinished(report) => {
+                self.set_status("Benchmark finished");
+                self.benchmark_report = Some(report);
+            }
+            AppEvent::TagIndex(tags) => {
+                if tags.is_empty() {
+                    self.set_status("No note has frontmatter tags");
+                    return;
+                }
+                self.set_status(format!("{} tags", tags.len()));
+                self.tag_browser = Some(TagBrowser { tags, selected: 0 });
+            }
+            AppEvent::IndexHealth {
+                total,
+                missing_full_text,
+                missing_vectors,
+            } => {
+                self.index_health_running = false;
+                self.show_index_health(total, missing_full_text, missing_vectors);
+            }
+            AppEvent::ResultSnippets { query, snippets } => {
+                // Snippets for an older query would point at the wrong matches
+                if query == self.search_query {
+                    self.result_snippets.extend(snippets);
+                }
+            }
+            AppEvent::RelatedFiles {
+                path,
+                result,
+                prewarm,
+                generation,
+            } => {
+                if let Ok(results) = &result {
+                    if !results.is_empty() {
+                        self.related_cache.insert(path.clone(), results.clone());
+                    }
+                }
+                // A warm-up only fills the cache, unless the user has since started
+                // waiting for this very note
+                if prewarm {
+                    self.prewarming_path = None;
+                    let awaited = self.is_loading_related_files
+                        && self.current_related_document_path.as_deref() == Some(path.as_str());
+                    if !awaited {
+                        return;
+                    }
+                } else if generation != self.related_generation.load(Ordering::SeqCst) {
+                    // Cancelled by `cancel_related_lookup`, which already reset the state
+                    return;
+                }
+                // The selection moved on while this request was running; drop the
+                // stale results and let `process` load the current document
+                if self.current_related_document_path.as_deref() != Some(path.as_str()) {
+                    self.is_loading_related_files = false;
+                    self.current_related_document_path = None;
+                    return;
+                }
+                match result {
+                    Ok(results) => {
+                        if results.is_empty() {
+                            self.related_files_error = Some(
+                                "No related documents found that meet the similarity threshold."
+                                    .to_string(),
+                            );
+                        } else {
+                            self.related_overlap = related_overlap(&path, &results);
+                            self.related_files = results;
+                            self.related_selected = 0;
+                            self.related_files_error = None;
+                            self.sort_related_files();
+                        }
+                    }
+                    Err(error_msg) => {
+                        self.related_files_error = Some(error_msg);
+                    }
+                }
+                self.is_loading_related_files = false;
+            }
+            AppEvent::SemanticResults {
+                query,
+                result,
+                generation,
+            } => {
+                // Ignore replies for a search that has since been replaced; the
+                // replacement already reset `is_semantic_searching`
+                if generation != self.semantic_generation.load(Ordering::SeqCst) {
+                    return;
+                }
+                if self.search_mode != SearchMode::Semantic {
+                    self.is_semantic_searching = false;
+                    return;
+                }
+                match result {
+                    Ok(mut results) => {
+                        crate::app::debug_log::log(format!(
+                            "Semantic search {:?}: {} results",
+                            query,
+                            results.len()
+                        ));
+                        self.rank_by_priority(&mut results);
+                        self.replace_search_results(results);
+                        self.semantic_search_error = None;
+                        self.prewarm_related();
+                    }
+                    Err(error_msg) => {
+                        self.search_results.clear();
+                        self.selected_search_index = 0;
+                        self.semantic_search_error = Some(error_msg);
+                    }
+                }
+                self.is_semantic_searching = false;
+            }
+        }
     }
 
     fn handle_key(
@@ -344,13 +2182,56 @@ impl App {
         key: KeyEvent,
         terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
     ) {
+        if key.code == KeyCode::Esc {
+            let window = Duration::from_millis(self.settings.double_esc_ms);
+            let double = self.settings.double_esc_ms > 0
+                && self.last_esc.is_some_and(|last| last.elapsed() <= window);
+            if double {
+                // A third press starts a new pair instead of going home again
+                self.last_esc = None;
+                self.go_home();
+                return;
+            }
+            self.last_esc = Some(Instant::now());
+        }
         match self.state {
             AppState::Search => self.handle_search_key(key, terminal),
             AppState::CommandPalette => self.handle_command_palette_key(key, terminal),
+            AppState::ConfigPreview => self.handle_config_preview_key(key, terminal),
             _ => self.handle_default_key(key),
         }
     }
 
+    /// Closes every popup and nested view and returns to the Preview screen. Also
+    /// keeps the second Esc of a pair from quitting once the first one got there.
+    fn go_home(&mut self) {
+        if !matches!(
+            self.state,
+            AppState::Search
+                | AppState::CommandPalette
+                | AppState::ConfigPreview
+                | AppState::Preview
+        ) {
+            // Scans and indexing runs finish on their own
+            return;
+        }
+        self.preview_diff = None;
+        self.link_picker = None;
+        self.link_graph = None;
+        self.quick_look = None;
+        self.link_inserter = None;
+        self.confirmation = None;
+        self.saved_search_selected = None;
+        self.tag_browser = None;
+        self.send_to_selected = None;
+        self.config_preview = None;
+        self.palette_stack.clear();
+        self.palette_filter.clear();
+        self.palette_search_active = false;
+        self.input_mode = InputMode::Normal;
+        self.state = AppState::Preview;
+    }
+
     fn handle_default_key(&mut self, key: KeyEvent) {
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc | KeyCode::Char('q'))
@@ -360,75 +2241,1456 @@ impl App {
         }
     }
 
-    // Delegate command palette key events to a helper in the ui module.
-    fn handle_command_palette_key(
-        &mut self,
-        key: KeyEvent,
-        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
-    ) {
-        // The implementation is now moved to the command_palette module.
-        crate::app::command_palette::handle_command_palette_key(self, key, terminal);
+    /// Loads the config file into a highlighted, read-only preview.
+    pub fn open_config_preview(&mut self) {
+        let Some(path) = config_editor::config_path() else {
+            self.set_status("No config file found");
+            self.state = self.palette_return_state;
+            return;
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let token = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("yaml");
+                self.config_preview = Some(ConfigPreview {
+                    path: path.display().to_string(),
+                    lines: crate::app::highlight::highlight_code(&content, token, &self.code_theme),
+                    scroll: 0,
+                });
+                self.state = AppState::ConfigPreview;
+            }
+            Err(e) => {
+                self.set_status(format!("Could not read config: {}", e));
+                self.state = self.palette_return_state;
+            }
+        }
     }
 
-    pub fn enter_search_mode(
+    /// Shows the settings actually in use (console.yaml over the defaults) and
+    /// what the app worked out at runtime, in the config preview overlay.
+    pub fn open_resolved_settings(&mut self) {
+        let settings = match serde_yaml::to_string(&self.settings) {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.set_status(format!("Could not show settings: {}", e));
+                self.state = self.palette_return_state;
+                return;
+            }
+        };
+        let display = |path: Option<std::path::PathBuf>| {
+            path.map_or_else(|| "none".to_string(), |path| path.display().to_string())
+        };
+        let settings_file = Settings::path().filter(|path| path.exists());
+        let paths = self.scanned_paths();
+        let vault_root = if paths.is_empty() {
+            "not scanned yet".to_string()
+        } else {
+            self.vault_root().display().to_string()
+        };
+        let content = format!(
+            "# settings file: {}\n\
+             # notemancy config: {}\n\
+             # vault root: {}\n\
+             # scanned notes: {}\n\
+             # related files debounce: {:?}\n\
+             # code theme in use: {}\n\
+             \n{}",
+            display(settings_file),
+            display(config_editor::config_path()),
+            vault_root,
+            paths.len(),
+            self.debounce_duration,
+            self.code_theme,
+            settings
+        );
+        self.config_preview = Some(ConfigPreview {
+            path: "Resolved settings".to_string(),
+            lines: crate::app::highlight::highlight_code(&content, "yaml", &self.code_theme),
+            scroll: 0,
+        });
+        self.state = AppState::ConfigPreview;
+    }
+
+    fn handle_config_preview_key(
+        &mut self,
+        key: KeyEvent,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) {
+        let Some(ref mut preview) = self.config_preview else {
+            self.state = self.palette_return_state;
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.config_preview = None;
+                self.state = self.palette_return_state;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let max_scroll = preview.lines.len().saturating_sub(1) as u16;
+                preview.scroll = (preview.scroll + 1).min(max_scroll);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                preview.scroll = preview.scroll.saturating_sub(1);
+            }
+            KeyCode::Char('e') => {
+                // Drop into the editor; the preview is stale afterwards, so close it.
+                self.config_preview = None;
+                if let Err(e) = config_editor::open_config_in_editor(terminal) {
+                    self.set_status(format!("Error opening config: {}", e));
+                }
+                self.state = self.state_after_external(self.palette_return_state);
+            }
+            _ => {}
+        }
+    }
+
+    // Delegate command palette key events to a helper in the ui module.
+    fn handle_command_palette_key(
+        &mut self,
+        key: KeyEvent,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) {
+        // The implementation is now moved to the command_palette module.
+        crate::app::command_palette::handle_command_palette_key(self, key, terminal);
+    }
+
+    pub fn enter_search_mode(
         &mut self,
         terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
     ) {
         let _ = terminal;
-        self.state = AppState::Indexing;
-        self.search_query.clear();
+        self.start_search(String::new());
+    }
+
+    /// Indexes, then opens the search view with `query` already typed in.
+    fn start_search(&mut self, query: String) {
+        self.result_list_title = None;
+        self.search_query = query;
         self.search_results.clear();
         self.selected_search_index = 0;
+        self.indexing_return_state = None;
+        self.start_indexing();
+    }
+
+    /// Moves on from the welcome screen as configured by `startup_action`.
+    fn run_startup_action(&mut self) {
+        match self.settings.startup_action {
+            StartupAction::Preview => {}
+            StartupAction::Search => self.start_search(String::new()),
+            StartupAction::Palette => self.enter_command_palette(),
+            StartupAction::LastSession => {
+                let query = crate::app::settings::load_last_query().unwrap_or_default();
+                self.start_search(query);
+            }
+        }
+    }
+
+    /// Rebuilds the full-text index from scratch without entering search mode,
+    /// then returns to where the palette was opened from.
+    pub fn rebuild_search_index(&mut self) {
+        self.indexing_return_state = Some(self.palette_return_state);
+        self.start_indexing();
+    }
+
+    /// The note shown in the preview pane: the pinned one, else the selection.
+    pub fn preview_result(&self) -> Option<SearchResult> {
+        match self.preview_locked_path {
+            Some(ref path) => Some(note_result(path, String::new())),
+            None => self.search_results.get(self.selected_search_index).cloned(),
+        }
+    }
+
+    /// Pins the preview to the selected note, or unpins it.
+    fn toggle_preview_lock(&mut self) {
+        if self.preview_locked_path.take().is_some() {
+            self.set_status("Preview follows the selection");
+            return;
+        }
+        if let Some(result) = self.search_results.get(self.selected_search_index) {
+            self.preview_locked_path = Some(result.path.clone());
+            let message = format!("Preview pinned to {}", result.title);
+            self.set_status(message);
+        }
+    }
+
+    /// Reads the previewed note on a worker thread when the cached text
+    /// belongs to another note or the file changed since it was read. The
+    /// same note is only checked for changes once per tick.
+    fn refresh_preview_content(&mut self) {
+        if self.state != AppState::Search {
+            return;
+        }
+        let Some(result) = self.preview_result() else {
+            return;
+        };
+        if self.preview_loading.as_deref() == Some(result.path.as_str()) {
+            return;
+        }
+        let same_note = self
+            .preview_content
+            .as_ref()
+            .is_some_and(|cached| cached.path == result.path);
+        if same_note && !std::mem::take(&mut self.preview_check_due) {
+            return;
+        }
+        let modified = std::fs::metadata(&result.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        let cached = same_note
+            && self
+                .preview_content
+                .as_ref()
+                .is_some_and(|cached| cached.modified == modified);
+        if cached {
+            return;
+        }
+        let path = result.path.clone();
+        self.preview_loading = Some(path.clone());
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::PreviewContent {
+                    path: path.clone(),
+                    modified,
+                    content: "Error reading file: thread terminated unexpectedly".to_string(),
+                },
+            );
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => format!("Error reading file: {}", e),
+            };
+            let _ = tx.send(AppEvent::PreviewContent {
+                path,
+                modified,
+                content,
+            });
+        });
+    }
 
-        // Create a channel to communicate when indexing is done
-        let (tx, rx) = std::sync::mpsc::channel::<()>();
-        self.indexing_receiver = Some(rx);
+    /// Refreshes the indexes after a note was edited, as `reindex_after_edit` asks.
+    /// notemancy_core can only index the whole vault, so the edited note is picked
+    /// up by a rescan followed by a full-text rebuild (and re-embedding for `all`).
+    fn reindex_after_edit(&mut self) {
+        match self.settings.reindex_after_edit {
+            crate::app::settings::ReindexAfterEdit::Off => return,
+            crate::app::settings::ReindexAfterEdit::Search => {}
+            crate::app::settings::ReindexAfterEdit::All => self.reembed_after_index = true,
+        }
+        self.start_scan();
+    }
+
+    /// Would bring the selected note's current content into the full-text index.
+    /// notemancy_core's `SearchEngine` only has `index_all_documents`, so until it
+    /// gains a single-document call this explains the gap instead of rebuilding.
+    fn reindex_selected(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let message = format!(
+            "Can't reindex {} alone: the search engine only rebuilds the whole index \
+             (use Rebuild Search Index)",
+            result.title
+        );
+        self.set_status(message);
+    }
+
+    /// Times a scan, a full-text index build, a few searches and a related-files
+    /// lookup on a worker thread, to show where a slow vault spends its time.
+    pub fn start_benchmark(&mut self) {
+        self.state = self.palette_return_state;
+        let paths = self.scanned_paths();
+        let Some(sample) = paths.first().cloned() else {
+            self.set_status("No scanned notes yet");
+            return;
+        };
+        // Searching for note titles keeps the queries realistic for this vault
+        let queries: Vec<String> = paths
+            .iter()
+            .take(5)
+            .map(|path| note_result(path, String::new()).title)
+            .collect();
+        self.set_status("Running benchmark...");
+
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::BenchmarkFinished(vec![(
+                    "Benchmark".to_string(),
+                    Err("thread terminated unexpectedly".to_string()),
+                )]),
+            );
+            let mut report = Vec::new();
+            let timed = |f: &mut dyn FnMut() -> Result<(), String>| {
+                let started = Instant::now();
+                f().map(|()| started.elapsed())
+            };
+
+            report.push((
+                "Scan".to_string(),
+                timed(&mut || {
+                    let scanner = Scanner::from_config().map_err(|e| e.to_string())?;
+                    scanner.scan_markdown_files().map_err(|e| e.to_string())?;
+                    Ok(())
+                }),
+            ));
+
+            let engine = notemancy_core::search::init_search_engine().map_err(|e| e.to_string());
+            report.push((
+                "Full-text index build".to_string(),
+                timed(&mut || {
+                    let db = notemancy_core::db::Database::new().map_err(|e| e.to_string())?;
+                    let engine = engine.as_ref().map_err(|e| e.clone())?;
+                    engine
+                        .index_all_documents(&db)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }),
+            ));
+            report.push((
+                format!("{} searches", queries.len()),
+                timed(&mut || {
+                    let engine = engine.as_ref().map_err(|e| e.clone())?;
+                    for query in &queries {
+                        engine.search(query, 20).map_err(|e| e.to_string())?;
+                    }
+                    Ok(())
+                }),
+            ));
+
+            // The AI backend is timed once without retries, so a flaky backend shows up
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let started = Instant::now();
+                let ai = match notemancy_core::config::load_config() {
+                    Ok(config) => notemancy_core::ai::AI::new(&config)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                report.push((
+                    "AI backend startup".to_string(),
+                    ai.as_ref()
+                        .map(|_| started.elapsed())
+                        .map_err(|e| e.clone()),
+                ));
+                let started = Instant::now();
+                let lookup = match (ai, std::fs::read_to_string(&sample)) {
+                    (Ok(ai), Ok(content)) => ai
+                        .find_similar_documents(&content, 20, None)
+                        .await
+                        .map(|_| started.elapsed())
+                        .map_err(|e| e.to_string()),
+                    (Err(e), _) => Err(e),
+                    (_, Err(e)) => Err(format!("Could not read {}: {}", sample, e)),
+                };
+                report.push(("Related-files lookup".to_string(), lookup));
+            });
+
+            let _ = tx.send(AppEvent::BenchmarkFinished(report));
+        });
+    }
+
+    /// Runs `index_all_documents` on a worker thread behind the indexing screen.
+    fn start_indexing(&mut self) {
+        self.state = AppState::Indexing;
+
+        // The scan tells us how many documents the index will cover.
+        let total = self.scan_result.as_ref().map_or(0, |files| files.len());
+        self.indexing_total = total;
+        self.indexing_started = Instant::now();
+        crate::app::debug_log::log(format!("Indexing {} notes", total));
+
+        // Completion is reported over the shared channel
+        let tx = self.event_tx.clone();
 
         // Create a separate thread to handle indexing
         thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::IndexingFinished(Some("thread terminated unexpectedly".to_string())),
+            );
+
             // Initialize the database
-            match notemancy_core::db::Database::new() {
+            let error = match notemancy_core::db::Database::new() {
                 Ok(db) => {
                     // Initialize a new search engine instance in this thread
                     match notemancy_core::search::init_search_engine() {
                         Ok(engine) => {
-                            // Index all documents from the database
-                            if let Err(e) = engine.index_all_documents(&db) {
-                                eprintln!("Indexing error: {}", e);
-                            }
+                            // Index all documents from the database. The core engine indexes
+                            // in one call with no progress callback; the "Indexed N / M"
+                            // gauge waits on a per-document callback variant in notemancy-core.
+                            engine
+                                .index_all_documents(&db)
+                                .err()
+                                .map(|e| format!("Indexing error: {}", e))
                         }
-                        Err(e) => eprintln!("Failed to initialize search engine: {}", e),
+                        Err(e) => Some(format!("Failed to initialize search engine: {}", e)),
                     }
                 }
-                Err(e) => eprintln!("Failed to connect to database: {}", e),
+                Err(e) => Some(format!("Failed to connect to database: {}", e)),
+            };
+
+            // Signal that indexing is complete
+            let _ = tx.send(AppEvent::IndexingFinished(error));
+        });
+    }
+
+    fn perform_search(&mut self) {
+        // A new query starts over at the configured page size
+        self.result_limit = self.settings.search_limit.max(1);
+        // New results are shown from the top
+        self.results_list_state = ratatui::widgets::ListState::default();
+        self.run_search();
+    }
+
+    /// Searches for the selected result's title, so a found note becomes the
+    /// next query. The replaced query can be brought back with `previous_query`.
+    fn search_from_selected(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let title = result.title.clone();
+        if title.is_empty() || title == self.search_query {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.search_query, title);
+        self.previous_queries.push(previous);
+        self.selected_search_index = 0;
+        self.preview_scroll = 0;
+        self.perform_search();
+        let message = format!("Searching for \"{}\"", self.search_query);
+        self.set_status(message);
+    }
+
+    /// Goes back to the query that `search_from_selected` replaced.
+    fn previous_query(&mut self) {
+        let Some(query) = self.previous_queries.pop() else {
+            self.set_status("No previous query");
+            return;
+        };
+        self.search_query = query;
+        self.selected_search_index = 0;
+        self.preview_scroll = 0;
+        self.perform_search();
+        let message = format!("Back to \"{}\"", self.search_query);
+        self.set_status(message);
+    }
+
+    /// Whether the last query filled its limit, so asking for more may find more.
+    pub fn more_results_available(&self) -> bool {
+        self.result_list_title.is_none()
+            && !self.search_query.is_empty()
+            && self.search_results.len() >= self.result_limit
+    }
+
+    /// Re-runs the query with a larger limit. The selection stays on the same note.
+    fn load_more_results(&mut self) {
+        if !self.more_results_available() {
+            return;
+        }
+        self.result_limit += self.settings.search_limit.max(1);
+        self.run_search();
+    }
+
+    /// Queries the active backend for `result_limit` results.
+    fn run_search(&mut self) {
+        // A typed query replaces any command-produced result list, and the
+        // results of a semantic search still running
+        self.cancel_semantic_search();
+        self.result_list_title = None;
+        // New results move the selection; let it settle before it enters the jump list
+        self.last_selection_change = Instant::now();
+        self.result_snippets.clear();
+
+        if self.search_query.is_empty() {
+            self.search_results.clear();
+            self.selected_search_index = 0;
+            self.preview_scroll = 0;
+            return;
+        }
+
+        if self.search_mode == SearchMode::Semantic {
+            self.perform_semantic_search();
+            return;
+        }
+
+        if let Some(ref search_engine) = self.search_engine {
+            let started = Instant::now();
+            match search_engine.search(&self.search_query, self.result_limit) {
+                Ok(mut results) => {
+                    crate::app::debug_log::log(format!(
+                        "Lexical search {:?}: {} results in {:.1?}",
+                        self.search_query,
+                        results.len(),
+                        started.elapsed()
+                    ));
+                    self.rank_by_priority(&mut results);
+                    self.replace_search_results(results);
+                    self.extract_result_snippets();
+                    self.prewarm_related();
+                }
+                Err(e) => {
+                    self.set_status(format!("Search error: {}", e));
+                    self.search_results.clear();
+                }
+            }
+        } else {
+            self.set_status("Search engine not configured!");
+            self.search_results.clear();
+        }
+    }
+
+    /// How `run_search` handles the current query, e.g.
+    /// `mode=lexical, terms='foo', limit=20, priority boost=0.2 from [priority]`.
+    /// notemancy_core parses the query itself, so the terms are shown as sent.
+    pub fn query_interpretation(&self) -> Option<String> {
+        if self.search_query.is_empty() {
+            return None;
+        }
+        let interpretation = match self.search_mode {
+            SearchMode::Semantic => format!(
+                "mode=semantic, text='{}' embedded as a whole, limit={}",
+                self.search_query, self.result_limit
+            ),
+            SearchMode::Lexical => {
+                let mut parts = vec![
+                    "mode=lexical".to_string(),
+                    format!("terms='{}'", self.search_query),
+                    format!("limit={}", self.result_limit),
+                ];
+                if self.settings.priority_weight != 0.0 {
+                    parts.push(format!(
+                        "priority boost={} from [{}]",
+                        self.settings.priority_weight,
+                        self.settings.priority_fields.join(", ")
+                    ));
+                }
+                parts.join(", ")
+            }
+        };
+        Some(interpretation)
+    }
+
+    /// Boosts results by the priority in their frontmatter, then re-sorts by score.
+    fn rank_by_priority(&mut self, results: &mut [SearchResult]) {
+        if self.settings.priority_weight == 0.0 {
+            return;
+        }
+        let weight = self.settings.priority_weight;
+        for result in results.iter_mut() {
+            if let Some(priority) = self.note_priority(&result.path) {
+                result.score *= (1.0 + weight * priority).max(0.0);
+            }
+        }
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// A note's frontmatter priority, read once and kept until a rescan or the
+    /// preview notices the note changed, so searching as you type touches no files.
+    fn note_priority(&mut self, path: &str) -> Option<f32> {
+        if let Some((_, priority)) = self.priority_cache.get(path) {
+            return *priority;
+        }
+        let modified = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        let priority = std::fs::read_to_string(path).ok().and_then(|content| {
+            crate::app::frontmatter::numeric_field(&content, &self.settings.priority_fields)
+        });
+        self.priority_cache
+            .insert(path.to_string(), (modified, priority));
+        priority
+    }
+
+    /// Reads the tags of results that aren't cached yet. Like priorities, cached
+    /// tags are kept until a rescan or the preview notices the note changed.
+    fn cache_result_tags(&mut self) {
+        if !self.settings.result_tags {
+            return;
+        }
+        for result in &self.search_results {
+            if self.tag_cache.contains_key(&result.path) {
+                continue;
+            }
+            let modified = std::fs::metadata(&result.path)
+                .and_then(|meta| meta.modified())
+                .ok();
+            let tags = std::fs::read_to_string(&result.path)
+                .map(|content| crate::app::frontmatter::tags(&content))
+                .unwrap_or_default();
+            self.tag_cache.insert(result.path.clone(), (modified, tags));
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Swaps in a new result set, keeping the selection on the same note when it
+    /// is still present and falling back to the top result otherwise.
+    fn replace_search_results(&mut self, results: Vec<SearchResult>) {
+        let selected_path = self
+            .search_results
+            .get(self.selected_search_index)
+            .map(|result| result.path.clone());
+        self.search_results = results;
+        self.selected_search_index = selected_path
+            .as_ref()
+            .and_then(|path| self.search_results.iter().position(|r| &r.path == path))
+            .unwrap_or(0);
+        let now_selected = self
+            .search_results
+            .get(self.selected_search_index)
+            .map(|result| &result.path);
+        if now_selected != selected_path.as_ref() {
+            self.preview_scroll = 0;
+        }
+    }
+
+    /// Pulls match contexts for the current query out of each result's file on a
+    /// worker thread, so slow reads don't hold up typing.
+    fn extract_result_snippets(&mut self) {
+        let query = self.search_query.clone();
+        let paths: Vec<String> = self
+            .search_results
+            .iter()
+            .map(|result| result.path.clone())
+            .collect();
+        let context_chars = self.settings.snippet_context_chars;
+        let max_fragments = self.settings.snippet_max_fragments;
+        let tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::ResultSnippets {
+                    query: query.clone(),
+                    snippets: HashMap::new(),
+                },
+            );
+            let snippets = paths
+                .into_iter()
+                .filter_map(|path| {
+                    let content = std::fs::read_to_string(&path).ok()?;
+                    let snippet = crate::app::highlight::extract_snippet(
+                        &content,
+                        &query,
+                        context_chars,
+                        max_fragments,
+                    )?;
+                    Some((path, snippet))
+                })
+                .collect();
+            let _ = tx.send(AppEvent::ResultSnippets { query, snippets });
+        });
+    }
+
+    /// Embeds the raw query and searches for similar notes on a worker thread.
+    fn perform_semantic_search(&mut self) {
+        let query = self.search_query.clone();
+        crate::app::debug_log::log(format!("Semantic search {:?} started", query));
+        let limit = self.result_limit;
+        let score_kind = self.settings.embedding_score;
+        let tx = self.event_tx.clone();
+        self.is_semantic_searching = true;
+        self.semantic_search_error = None;
+        let current_generation = Arc::clone(&self.semantic_generation);
+        let generation = current_generation.load(Ordering::SeqCst);
+
+        std::thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::SemanticResults {
+                    query: query.clone(),
+                    result: Err("Search thread terminated unexpectedly".to_string()),
+                    generation,
+                },
+            );
+            // Initialize runtime for async operations
+            let rt = tokio::runtime::Runtime::new().unwrap();
+
+            rt.block_on(async {
+                let result = match notemancy_core::config::load_config() {
+                    Ok(config) => match log_ai_init(
+                        "Semantic search",
+                        notemancy_core::ai::AI::new(&config).await,
+                    ) {
+                        Ok(ai) => match ai.find_similar_documents(&query, limit, None).await {
+                            Ok(similar_docs) => {
+                                let mut results: Vec<SearchResult> = similar_docs
+                                    .into_iter()
+                                    .filter_map(|(doc, score)| {
+                                        doc.metadata.get("physical_path").map(|rel_path| {
+                                            similarity_result(rel_path, score, score_kind)
+                                        })
+                                    })
+                                    .collect();
+                                results.sort_by(|a, b| {
+                                    b.score
+                                        .partial_cmp(&a.score)
+                                        .unwrap_or(std::cmp::Ordering::Equal)
+                                });
+                                Ok(results)
+                            }
+                            Err(e) => Err(format!("Error finding similar documents: {}", e)),
+                        },
+                        Err(e) => Err(format!("Error initializing AI: {}", e)),
+                    },
+                    Err(e) => Err(format!("Error loading config: {}", e)),
+                };
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let _ = tx.send(AppEvent::SemanticResults {
+                    query,
+                    result,
+                    generation,
+                });
+            });
+        });
+    }
+
+    /// Abandons the semantic search in flight; its worker sees the bumped
+    /// generation and never sends its results.
+    fn cancel_semantic_search(&mut self) {
+        self.semantic_generation.fetch_add(1, Ordering::SeqCst);
+        self.is_semantic_searching = false;
+    }
+
+    /// Adds the selected note to the jump list once the selection has settled.
+    fn record_navigation(&mut self) {
+        let Some(current) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let at_current = self
+            .nav_cursor
+            .checked_sub(1)
+            .and_then(|i| self.nav_history.get(i))
+            .is_some_and(|entry| entry.path == current.path);
+        if at_current || self.last_selection_change.elapsed() < self.debounce_duration {
+            return;
+        }
+
+        // Visiting a new note drops the forward history, like an editor jumplist
+        let entry = NavEntry {
+            path: current.path.clone(),
+            query: self.search_query.clone(),
+            results: self.search_results.clone(),
+            selected: self.selected_search_index,
+            result_list_title: self.result_list_title.clone(),
+        };
+        let path = entry.path.clone();
+        self.nav_history.truncate(self.nav_cursor);
+        self.nav_history.push(entry);
+        self.nav_cursor = self.nav_history.len();
+
+        if self.settings.track_seen && self.seen_notes.insert(path.clone()) {
+            if let Err(e) = crate::app::settings::save_seen_note(&path) {
+                self.set_status(format!("Could not remember seen note: {}", e));
+            }
+        }
+    }
+
+    fn navigate_back(&mut self) {
+        if self.nav_cursor < 2 {
+            self.set_status("No earlier notes in history");
+            return;
+        }
+        self.nav_cursor -= 1;
+        self.restore_navigation();
+    }
+
+    fn navigate_forward(&mut self) {
+        if self.nav_cursor >= self.nav_history.len() {
+            self.set_status("No later notes in history");
+            return;
+        }
+        self.nav_cursor += 1;
+        self.restore_navigation();
+    }
+
+    /// Restores the query, results and selection of the entry under the cursor.
+    fn restore_navigation(&mut self) {
+        let entry = &self.nav_history[self.nav_cursor - 1];
+        self.search_query = entry.query.clone();
+        self.search_results = entry.results.clone();
+        self.selected_search_index = entry.selected;
+        self.result_list_title = entry.result_list_title.clone();
+        self.input_mode = InputMode::Normal;
+        self.preview_scroll = 0;
+        let status = format!(
+            "History {}/{}: {}",
+            self.nav_cursor,
+            self.nav_history.len(),
+            entry.path
+        );
+        self.set_status(status);
+    }
+
+    /// Pages the preview to the next or previous heading, or by a screenful of
+    /// rows when `by_rows` is set (Shift+PageDown/PageUp).
+    fn page_preview(&mut self, down: bool, by_rows: bool) {
+        let layout = &self.preview_layout;
+        let scroll = self.preview_scroll as usize;
+        let page = layout.page_height.max(1);
+        let target = match (by_rows, down) {
+            (true, true) => scroll + page,
+            (true, false) => scroll.saturating_sub(page),
+            (false, true) => layout
+                .heading_rows
+                .iter()
+                .copied()
+                .find(|&row| row > scroll)
+                .unwrap_or(scroll + page),
+            (false, false) => layout
+                .heading_rows
+                .iter()
+                .rev()
+                .copied()
+                .find(|&row| row < scroll)
+                .unwrap_or(0),
+        };
+        let max_scroll = layout.total_rows.saturating_sub(page);
+        self.preview_scroll = target.min(max_scroll).min(u16::MAX as usize) as u16;
+    }
+
+    /// Scrolls the preview by `rows`, stopping once the last row is on screen.
+    fn scroll_preview_by(&mut self, rows: isize) {
+        let layout = &self.preview_layout;
+        let max_scroll = layout.total_rows.saturating_sub(layout.page_height.max(1));
+        let target = (self.preview_scroll as usize).saturating_add_signed(rows);
+        self.preview_scroll = target.min(max_scroll).min(u16::MAX as usize) as u16;
+    }
+
+    fn toggle_search_mode(&mut self) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Lexical => SearchMode::Semantic,
+            SearchMode::Semantic => SearchMode::Lexical,
+        };
+        self.semantic_search_error = None;
+        self.perform_search();
+    }
+
+    /// Switches between embedding-only and hybrid ranking of related files.
+    fn toggle_related_ranking(&mut self) {
+        self.related_ranking = match self.related_ranking {
+            RelatedRanking::Embedding => RelatedRanking::Hybrid,
+            RelatedRanking::Hybrid => RelatedRanking::Embedding,
+        };
+        self.sort_related_files();
+    }
+
+    /// Orders the related files by the active ranking, best first.
+    fn sort_related_files(&mut self) {
+        let ranking = self.related_ranking;
+        let overlap = &self.related_overlap;
+        let rank = |result: &SearchResult| match ranking {
+            RelatedRanking::Embedding => result.score,
+            RelatedRanking::Hybrid => {
+                let lexical = overlap.get(&result.path).copied().unwrap_or(0.0);
+                (1.0 - HYBRID_LEXICAL_WEIGHT) * result.score + HYBRID_LEXICAL_WEIGHT * lexical
+            }
+        };
+        self.related_files.sort_by(|a, b| {
+            rank(b)
+                .partial_cmp(&rank(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Switches to the next supported similarity metric and reloads related files.
+    fn cycle_similarity_metric(&mut self) {
+        if SUPPORTED_METRICS.len() < 2 {
+            self.set_status(format!(
+                "Only {} similarity is supported by notemancy-core",
+                self.similarity_metric.label()
+            ));
+            return;
+        }
+        let current = SUPPORTED_METRICS
+            .iter()
+            .position(|m| *m == self.similarity_metric)
+            .unwrap_or(0);
+        self.similarity_metric = SUPPORTED_METRICS[(current + 1) % SUPPORTED_METRICS.len()];
+        // Forget the loaded document so `process` fetches it again with the new metric
+        self.current_related_document_path = None;
+        self.set_status(format!(
+            "Similarity metric: {}",
+            self.similarity_metric.label()
+        ));
+    }
+
+    fn toggle_preview_line_numbers(&mut self) {
+        self.settings.preview_line_numbers = !self.settings.preview_line_numbers;
+        if let Err(e) = self.settings.save() {
+            self.set_status(format!("Could not save settings: {}", e));
+        }
+    }
+
+    /// Switches the preview to the next bundled code theme without saving it.
+    fn cycle_code_theme(&mut self) {
+        let names = crate::app::highlight::theme_names();
+        let next = names
+            .iter()
+            .position(|name| *name == self.code_theme)
+            .map_or(0, |i| (i + 1) % names.len());
+        if let Some(name) = names.get(next) {
+            self.code_theme = name.clone();
+            self.related_preview = None;
+            self.set_status(format!("Theme: {} (T to save)", name));
+        }
+    }
+
+    /// Makes the code theme being previewed the configured one.
+    fn save_code_theme(&mut self) {
+        self.settings.code_theme = Some(self.code_theme.clone());
+        match self.settings.save() {
+            Ok(()) => self.set_status(format!("Saved theme {}", self.code_theme)),
+            Err(e) => self.set_status(format!("Could not save settings: {}", e)),
+        }
+    }
+
+    fn toggle_preview_metadata(&mut self) {
+        self.settings.preview_metadata = !self.settings.preview_metadata;
+        if let Err(e) = self.settings.save() {
+            self.set_status(format!("Could not save settings: {}", e));
+        }
+    }
+
+    /// Opens the link picker for the selected note, listing every URL it contains.
+    fn open_link_picker(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let urls = match std::fs::read_to_string(&result.path) {
+            Ok(content) => crate::app::highlight::extract_urls(&content),
+            Err(e) => {
+                self.set_status(format!("Could not read note: {}", e));
+                return;
+            }
+        };
+        if urls.is_empty() {
+            self.set_status("No links in this note");
+            return;
+        }
+        self.link_picker = Some(LinkPicker {
+            links: urls,
+            selected: 0,
+            wikilinks: false,
+        });
+    }
+
+    /// Opens the link picker on the `[[wikilinks]]` of the selected note.
+    fn open_wikilink_picker(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let mut targets = match std::fs::read_to_string(&result.path) {
+            Ok(content) => crate::app::links::wikilink_targets(&content),
+            Err(e) => {
+                self.set_status(format!("Could not read note: {}", e));
+                return;
+            }
+        };
+        // Keep the first occurrence of each target, in note order
+        let mut seen = HashSet::new();
+        targets.retain(|target| seen.insert(target.to_lowercase()));
+        if targets.is_empty() {
+            self.set_status("No wikilinks in this note");
+            return;
+        }
+        self.link_picker = Some(LinkPicker {
+            links: targets,
+            selected: 0,
+            wikilinks: true,
+        });
+    }
+
+    /// Selects the note a wikilink points to, resolving its title against the scanned notes.
+    fn follow_wikilink(&mut self, target: &str) {
+        let Some(from) = self
+            .search_results
+            .get(self.selected_search_index)
+            .map(|result| result.path.clone())
+        else {
+            return;
+        };
+        let resolver = crate::app::links::LinkResolver::new(&self.scanned_paths());
+        let link = crate::app::links::LinkTarget::Wiki(target.to_string());
+        let Some(path) = resolver.resolve(&link, &from) else {
+            self.set_status(format!("No note named {}", target));
+            return;
+        };
+        let title = format!("Linked from {}", note_result(&from, String::new()).title);
+        self.pivot_to_note(&path, title);
+    }
+
+    /// Selects the note at `input`, a path relative to the vault root, absolute, or
+    /// starting with `~/`, after checking it is a markdown file inside the vault.
+    fn go_to_path(&mut self, input: &str) {
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        let root = self.vault_root();
+        let path = match input.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None if std::path::Path::new(input).is_absolute() => std::path::PathBuf::from(input),
+            None => root.join(input),
+        };
+        let Ok(canonical) = std::fs::canonicalize(&path) else {
+            self.set_status(format!("No such file: {}", path.display()));
+            return;
+        };
+        if !canonical.is_file() {
+            self.set_status(format!("Not a file: {}", path.display()));
+            return;
+        }
+        if canonical.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            self.set_status(format!("Not a markdown note: {}", path.display()));
+            return;
+        }
+        let inside_vault =
+            std::fs::canonicalize(&root).is_ok_and(|root| canonical.starts_with(root));
+        if !inside_vault {
+            self.set_status(format!(
+                "Outside the vault ({}): {}",
+                root.display(),
+                path.display()
+            ));
+            return;
+        }
+        // Use the scanned spelling of the path so it matches results and caches
+        let note = paths
+            .into_iter()
+            .find(|p| std::fs::canonicalize(p).is_ok_and(|p| p == canonical))
+            .unwrap_or_else(|| path.display().to_string());
+        let title = note_result(&note, String::new()).title;
+        self.pivot_to_note(&note, title);
+        self.input_mode = InputMode::Normal;
+        self.state = AppState::Search;
+    }
+
+    /// A writing session: opens the note at `input`, relative to the vault and
+    /// created with a title heading when new, in the editor with nothing else on
+    /// screen. Afterwards the note is selected in search and the vault reindexed.
+    fn start_writing(
+        &mut self,
+        input: &str,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let paths = self.scanned_paths();
+        if paths.is_empty() {
+            self.set_status("No scanned notes yet");
+            return;
+        }
+        let relative = std::path::Path::new(input);
+        let escapes = relative
+            .components()
+            .any(|component| !matches!(component, std::path::Component::Normal(_)));
+        if escapes {
+            self.set_status("Give a path inside the vault, e.g. journal/ideas");
+            return;
+        }
+        let mut path = self.vault_root().join(relative);
+        if path.extension().is_none() {
+            path.set_extension("md");
+        }
+        let created = !path.exists();
+        if created {
+            let title = path
+                .file_stem()
+                .map_or_else(String::new, |s| s.to_string_lossy().to_string());
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, format!("# {}\n\n", title)));
+            if let Err(e) = written {
+                self.set_status(format!("Could not create {}: {}", path.display(), e));
+                return;
+            }
+        }
+
+        let note = path.display().to_string();
+        let edited = crate::config_editor::open_file_in_editor(terminal, &note, false);
+        if created && !edited.unwrap_or(false) {
+            // Nothing was written; don't leave the stub behind
+            let _ = std::fs::remove_file(&path);
+            self.state = self.state_after_external(self.state);
+            self.set_status("Nothing written");
+            return;
+        }
+        let title = note_result(&note, String::new()).title;
+        self.pivot_to_note(&note, title);
+        self.input_mode = InputMode::Normal;
+        self.state = AppState::Search;
+        if edited.unwrap_or(false) {
+            self.start_scan();
+        }
+    }
+
+    /// Selects `path` in the results, or shows it as a one-note list titled `title`
+    /// when the current results don't include it.
+    fn pivot_to_note(&mut self, path: &str, title: String) {
+        // Stay in the current list when the note is already in it
+        if let Some(index) = self.search_results.iter().position(|r| r.path == path) {
+            self.selected_search_index = index;
+            self.preview_scroll = 0;
+            self.last_selection_change = Instant::now();
+            return;
+        }
+        self.show_result_list(title, vec![note_result(path, String::new())]);
+    }
+
+    /// Opens the quick look overlay on the selected result.
+    fn open_quick_look(&mut self) {
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        let content = match std::fs::read_to_string(&result.path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_status(format!("Could not read note: {}", e));
+                return;
+            }
+        };
+        let mut lines =
+            crate::app::highlight::render_markdown(&content, self.render_options()).lines;
+        // Land on the first occurrence of the query, like the preview highlights it
+        let mut matches = Vec::new();
+        if !self.search_query.is_empty() {
+            for (i, line) in lines.iter_mut().enumerate() {
+                let text: String = line
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect();
+                if !crate::app::highlight::find_matches(&text, &self.search_query).is_empty() {
+                    matches.push(i);
+                    *line = crate::app::highlight::highlight_matches(line, &self.search_query);
+                }
+            }
+        }
+        self.quick_look = Some(QuickLook {
+            title: result.title.clone(),
+            lines,
+            scroll: 0,
+            jump_to: matches.first().copied(),
+            matches,
+            current_match: 0,
+        });
+    }
+
+    fn handle_quick_look_key(&mut self, key: KeyEvent) {
+        let Some(ref mut quick_look) = self.quick_look else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Char('q') => self.quick_look = None,
+            KeyCode::Down | KeyCode::Char('j') => {
+                quick_look.scroll = quick_look.scroll.saturating_add(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                quick_look.scroll = quick_look.scroll.saturating_sub(1);
+            }
+            KeyCode::PageDown => quick_look.scroll = quick_look.scroll.saturating_add(20),
+            KeyCode::PageUp => quick_look.scroll = quick_look.scroll.saturating_sub(20),
+            KeyCode::Char('n') | KeyCode::Char('N') if !quick_look.matches.is_empty() => {
+                let count = quick_look.matches.len();
+                quick_look.current_match = if key.code == KeyCode::Char('n') {
+                    (quick_look.current_match + 1) % count
+                } else {
+                    (quick_look.current_match + count - 1) % count
+                };
+                quick_look.jump_to = Some(quick_look.matches[quick_look.current_match]);
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the picker for a note to append a link to the selected result to.
+    fn open_link_inserter(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Some(result) = self.search_results.get(self.selected_search_index) else {
+            return;
+        };
+        self.link_inserter = Some(LinkInserter {
+            source: result.path.clone(),
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+        });
+    }
+
+    fn handle_link_inserter_key(&mut self, key: KeyEvent) {
+        let Some(ref mut inserter) = self.link_inserter else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.link_inserter = None,
+            KeyCode::Up => inserter.selected = inserter.selected.saturating_sub(1),
+            KeyCode::Down => {
+                if inserter.selected + 1 < inserter.results.len() {
+                    inserter.selected += 1;
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                inserter.query.push(c);
+                self.run_link_inserter_search();
+            }
+            KeyCode::Backspace => {
+                inserter.query.pop();
+                self.run_link_inserter_search();
+            }
+            KeyCode::Enter => {
+                let Some(target) = inserter.results.get(inserter.selected) else {
+                    return;
+                };
+                let target = target.path.clone();
+                let source = inserter.source.clone();
+                self.link_inserter = None;
+                self.insert_link(&source, &target);
+            }
+            _ => {}
+        }
+    }
+
+    /// Searches for link targets, leaving out the note being linked.
+    fn run_link_inserter_search(&mut self) {
+        let Some(ref mut inserter) = self.link_inserter else {
+            return;
+        };
+        inserter.selected = 0;
+        inserter.results.clear();
+        if inserter.query.is_empty() {
+            return;
+        }
+        if let Some(ref search_engine) = self.search_engine {
+            match search_engine.search(&inserter.query, 9) {
+                Ok(results) => {
+                    inserter.results = results
+                        .into_iter()
+                        .filter(|result| result.path != inserter.source)
+                        .take(8)
+                        .collect();
+                }
+                Err(e) => self.set_status(format!("Search error: {}", e)),
+            }
+        }
+    }
+
+    /// Appends a wikilink to `source` at the end of `target`.
+    fn insert_link(&mut self, source: &str, target: &str) {
+        let title = note_result(source, String::new()).title;
+        let target_title = note_result(target, String::new()).title;
+        match crate::app::links::append_wikilink(target, &title) {
+            Ok(()) => self.set_status(format!("Linked {} from {}", title, target_title)),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Opens the link graph popup for the selected note.
+    pub fn open_link_graph(&mut self) {
+        let Some(path) = self
+            .search_results
+            .get(self.selected_search_index)
+            .map(|result| result.path.clone())
+        else {
+            self.set_status("Select a note in the search view first");
+            return;
+        };
+        let paths = self.scanned_paths();
+        let outbound = crate::app::links::outbound_notes(&path, &paths);
+        let inbound = crate::app::links::inbound_notes(&path, &paths);
+        if outbound.is_empty() && inbound.is_empty() {
+            self.set_status("This note has no links in or out");
+            return;
+        }
+        self.link_graph = Some(LinkGraph {
+            path,
+            outbound,
+            inbound,
+            selected: 0,
+        });
+    }
+
+    fn handle_link_graph_key(&mut self, key: KeyEvent) {
+        let Some(ref mut graph) = self.link_graph else {
+            return;
+        };
+        let count = graph.outbound.len() + graph.inbound.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('g') => self.link_graph = None,
+            KeyCode::Up | KeyCode::Char('k') => graph.selected = graph.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                if graph.selected + 1 < count {
+                    graph.selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                let Some(target) = graph.entries().nth(graph.selected).cloned() else {
+                    return;
+                };
+                let title = format!("Links of {}", note_result(&graph.path, String::new()).title);
+                self.link_graph = None;
+                self.pivot_to_note(&target, title);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_link_picker_key(&mut self, key: KeyEvent) {
+        let Some(ref mut picker) = self.link_picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.link_picker = None,
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down => {
+                if picker.selected + 1 < picker.links.len() {
+                    picker.selected += 1;
+                }
+            }
+            KeyCode::Enter if picker.wikilinks => {
+                let target = picker.links[picker.selected].clone();
+                self.link_picker = None;
+                self.follow_wikilink(&target);
+            }
+            KeyCode::Enter => {
+                let url = picker.links[picker.selected].clone();
+                self.link_picker = None;
+                match crate::config_editor::open_url(&url) {
+                    Ok(()) => self.set_status(format!("Opened {}", url)),
+                    Err(e) => self.set_status(format!("Could not open {}: {}", url, e)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows the folder tree sidebar and focuses it, or hides it.
+    fn toggle_sidebar(&mut self) {
+        if self.sidebar_visible {
+            self.sidebar_visible = false;
+            self.sidebar_focused = false;
+            return;
+        }
+        if self.folder_tree.is_none() {
+            let paths = self.scanned_paths();
+            if paths.is_empty() {
+                self.set_status("No scanned notes to show");
+                return;
+            }
+            self.folder_tree = Some(crate::app::tree::FolderTree::build(&paths));
+        }
+        self.sidebar_visible = true;
+        self.sidebar_focused = true;
+    }
+
+    fn handle_sidebar_key(&mut self, key: KeyEvent) {
+        let Some(ref tree) = self.folder_tree else {
+            return;
+        };
+        let rows = tree.visible_rows(&self.tree_expanded);
+        // Collapsing a folder can leave the selection past the end
+        self.tree_selected = self.tree_selected.min(rows.len().saturating_sub(1));
+        let Some(row) = rows.get(self.tree_selected) else {
+            return;
+        };
+        match key.code {
+            // Hand the keyboard back to the results list; `b` hides the sidebar too
+            KeyCode::Esc => self.sidebar_focused = false,
+            KeyCode::Char('b') => self.toggle_sidebar(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.tree_selected = self.tree_selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.tree_selected + 1 < rows.len() {
+                    self.tree_selected += 1;
+                }
             }
-
-            // Signal that indexing is complete
-            let _ = tx.send(());
-        });
+            KeyCode::Left | KeyCode::Char('h') => {
+                if row.is_dir && row.expanded {
+                    self.tree_expanded.remove(&row.path);
+                } else if let Some(parent) = rows[..self.tree_selected]
+                    .iter()
+                    .rposition(|r| r.is_dir && r.depth + 1 == row.depth)
+                {
+                    // Jump to the enclosing folder
+                    self.tree_selected = parent;
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter if row.is_dir => {
+                if !self.tree_expanded.insert(row.path.clone()) && key.code == KeyCode::Enter {
+                    self.tree_expanded.remove(&row.path);
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
+                // Show the note's folder in the results list with the note selected
+                let note = row.path.to_string_lossy().to_string();
+                self.show_folder_of(&note);
+            }
+            _ => {}
+        }
     }
 
-    fn perform_search(&mut self) {
-        if self.search_query.is_empty() {
-            self.search_results.clear();
-            self.selected_search_index = 0;
+    /// Opens the "send to" menu for the selected note.
+    fn open_send_to_menu(&mut self) {
+        if self
+            .search_results
+            .get(self.selected_search_index)
+            .is_none()
+        {
+            return;
+        }
+        if self.settings.send_to.is_empty() {
+            self.set_status("No send_to commands configured in console.yaml");
             return;
         }
+        self.send_to_selected = Some(0);
+    }
 
-        if let Some(ref search_engine) = self.search_engine {
-            match search_engine.search(&self.search_query, 20) {
-                Ok(results) => {
-                    self.search_results = results;
-                    self.selected_search_index = 0;
+    fn handle_send_to_key(
+        &mut self,
+        key: KeyEvent,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) {
+        let Some(selected) = self.send_to_selected else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.send_to_selected = None,
+            KeyCode::Up => self.send_to_selected = Some(selected.saturating_sub(1)),
+            KeyCode::Down => {
+                if selected + 1 < self.settings.send_to.len() {
+                    self.send_to_selected = Some(selected + 1);
                 }
-                Err(e) => {
-                    eprintln!("Search error: {}", e);
-                    self.search_results.clear();
+            }
+            KeyCode::Enter => {
+                self.send_to_selected = None;
+                let Some(result) = self.search_results.get(self.selected_search_index) else {
+                    return;
+                };
+                let target = &self.settings.send_to[selected];
+                let name = target.name.clone();
+                let command = target.command_for(&result.path);
+                match crate::config_editor::run_external_command(terminal, &command) {
+                    Ok(()) => self.set_status(format!("Sent to {}", name)),
+                    Err(e) => self.set_status(format!("{} failed: {}", name, e)),
                 }
+                self.state = self.state_after_external(AppState::Search);
             }
-        } else {
-            eprintln!("Search engine not configured!");
-            self.search_results.clear();
+            _ => {}
         }
     }
 
@@ -437,17 +3699,78 @@ impl App {
         key: KeyEvent,
         terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
     ) {
+        if self.quick_look.is_some() {
+            self.handle_quick_look_key(key);
+            return;
+        }
+        if self.link_inserter.is_some() {
+            self.handle_link_inserter_key(key);
+            return;
+        }
+        if self.link_graph.is_some() {
+            self.handle_link_graph_key(key);
+            return;
+        }
+        if self.link_picker.is_some() {
+            self.handle_link_picker_key(key);
+            return;
+        }
+        if self.send_to_selected.is_some() {
+            self.handle_send_to_key(key, terminal);
+            return;
+        }
+        if self.sidebar_focused && self.input_mode == InputMode::Normal {
+            self.handle_sidebar_key(key);
+            return;
+        }
+
+        // Ctrl+T switches between lexical and semantic search in either input mode
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('t') {
+            self.toggle_search_mode();
+            return;
+        }
+
+        // Jump list: Ctrl+O goes back, Ctrl+I goes forward. Most terminals send
+        // Ctrl+I as Tab, so Ctrl+N is accepted for forward as well.
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('o') => {
+                    self.navigate_back();
+                    return;
+                }
+                KeyCode::Char('i') | KeyCode::Char('n') => {
+                    self.navigate_forward();
+                    return;
+                }
+                _ => {}
+            }
+        }
+
         match self.input_mode {
             InputMode::Normal => {
                 match key.code {
                     // In Normal mode, handle navigation and view toggling
                     KeyCode::Esc => {
-                        self.state = AppState::Preview;
+                        // Close a diff view before leaving search
+                        if self.preview_diff.take().is_none() {
+                            self.state = AppState::Preview;
+                        }
                     }
                     KeyCode::Enter => {
                         if let Some(doc) = self.search_results.get(self.selected_search_index) {
-                            let _ = crate::config_editor::open_file_in_editor(terminal, &doc.path);
-                            self.state = AppState::Preview;
+                            let path = doc.path.clone();
+                            if !self.ensure_note_exists(&path) {
+                                return;
+                            }
+                            let edited = crate::config_editor::open_file_in_editor(
+                                terminal,
+                                &path,
+                                self.settings.read_only,
+                            );
+                            self.state = self.state_after_external(AppState::Search);
+                            if edited.unwrap_or(false) {
+                                self.reindex_after_edit();
+                            }
                         }
                     }
                     KeyCode::Tab | KeyCode::Char('r') => {
@@ -462,41 +3785,172 @@ impl App {
                     
                         // If we just switched to RelatedFiles, update immediately
                         if self.detail_view_mode == DetailViewMode::RelatedFiles && !self.is_loading_related_files {
-                            // Clear any existing related files
-                            self.related_files.clear();
-                            self.related_files_error = None;
-                        
-                            // Set last selected index to current so we don't trigger again on the same item
-                            self.last_selected_index = self.selected_search_index;
-                        
-                            // Update immediately
-                            self.get_related_files_for_selected();
+                            // Keep the list and its selection when they already belong to this note
+                            let selected_path = self
+                                .search_results
+                                .get(self.selected_search_index)
+                                .map(|result| result.path.clone());
+                            if selected_path != self.current_related_document_path
+                                || self.related_files.is_empty()
+                            {
+                                // Clear any existing related files
+                                self.related_files.clear();
+                                self.related_files_error = None;
+
+                                // Set last selected index to current so we don't trigger again on the same item
+                                self.last_selected_index = self.selected_search_index;
+
+                                // Update immediately
+                                self.get_related_files_for_selected();
+                            }
                         }
                     }
                     KeyCode::Char('/') => {
                         // Enter editing mode with '/'
                         self.input_mode = InputMode::Editing;
                     }
+                    // j/k move within the active detail view, each of which keeps its own position
+                    KeyCode::Char('j') => match self.detail_view_mode {
+                        DetailViewMode::Preview => {
+                            self.scroll_preview_by(1);
+                        }
+                        DetailViewMode::RelatedFiles => {
+                            if self.related_selected + 1 < self.related_files.len() {
+                                self.related_selected += 1;
+                            }
+                        }
+                    },
+                    KeyCode::Char('k') => match self.detail_view_mode {
+                        DetailViewMode::Preview => {
+                            self.scroll_preview_by(-1);
+                        }
+                        DetailViewMode::RelatedFiles => {
+                            self.related_selected = self.related_selected.saturating_sub(1);
+                        }
+                    },
+                    KeyCode::PageDown => {
+                        self.page_preview(true, key.modifiers.contains(KeyModifiers::SHIFT));
+                    }
+                    KeyCode::PageUp => {
+                        self.page_preview(false, key.modifiers.contains(KeyModifiers::SHIFT));
+                    }
+                    KeyCode::Char('y') => {
+                        self.yank_permalink();
+                    }
+                    KeyCode::Char('Y') if self.detail_view_mode == DetailViewMode::RelatedFiles => {
+                        self.yank_related_links();
+                    }
+                    KeyCode::Char('o') => {
+                        self.open_link_picker();
+                    }
+                    KeyCode::Char('w') => {
+                        self.open_wikilink_picker();
+                    }
+                    KeyCode::Char('g') => {
+                        self.open_link_graph();
+                    }
+                    KeyCode::Char(' ') => {
+                        self.open_quick_look();
+                    }
+                    KeyCode::Char('+') => {
+                        self.load_more_results();
+                    }
+                    // Ctrl+D/Ctrl+U scroll the preview by half a screen
+                    KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let half = (self.preview_layout.page_height / 2).max(1);
+                        self.scroll_preview_by(half as isize);
+                    }
+                    KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let half = (self.preview_layout.page_height / 2).max(1);
+                        self.scroll_preview_by(-(half as isize));
+                    }
+                    KeyCode::Char('d') => {
+                        if let Some(result) = self.search_results.get(self.selected_search_index) {
+                            let note = result.path.clone();
+                            self.show_folder_of(&note);
+                        }
+                    }
+                    KeyCode::Char('s') => {
+                        self.open_send_to_menu();
+                    }
+                    KeyCode::Char('m') if self.detail_view_mode == DetailViewMode::RelatedFiles => {
+                        self.cycle_similarity_metric();
+                    }
+                    KeyCode::Char('h') if self.detail_view_mode == DetailViewMode::RelatedFiles => {
+                        self.toggle_related_ranking();
+                    }
+                    KeyCode::Char('f') => {
+                        self.focus_mode = !self.focus_mode;
+                    }
+                    KeyCode::Char('b') => {
+                        self.toggle_sidebar();
+                    }
+                    KeyCode::Char('#') => {
+                        self.toggle_preview_line_numbers();
+                    }
+                    KeyCode::Char('i') => {
+                        self.toggle_preview_metadata();
+                    }
+                    KeyCode::Char('p') => {
+                        self.toggle_preview_lock();
+                    }
+                    KeyCode::Char('t') => {
+                        self.cycle_code_theme();
+                    }
+                    KeyCode::Char('T') => {
+                        self.save_code_theme();
+                    }
+                    KeyCode::Char('L') => {
+                        self.open_link_inserter();
+                    }
+                    KeyCode::Char('S') => {
+                        self.search_from_selected();
+                    }
+                    KeyCode::Char('U') => {
+                        self.reindex_selected();
+                    }
+                    KeyCode::Char('F') if !self.search_query.is_empty() => {
+                        self.open_text_prompt("Save search as", PromptAction::SaveSearch);
+                    }
+                    KeyCode::Backspace => {
+                        self.previous_query();
+                    }
+                    // 1-9 jump to that result; Enter then opens it
+                    KeyCode::Char(c @ '1'..='9') => {
+                        let index = c as usize - '1' as usize;
+                        if index < self.search_results.len() && index != self.selected_search_index
+                        {
+                            self.selected_search_index = index;
+                            self.preview_scroll = 0;
+                            self.last_selection_change = Instant::now();
+                        }
+                    }
                     KeyCode::Up => {
                         if self.selected_search_index > 0 {
                             let old_selection = self.selected_search_index;
                             self.selected_search_index -= 1;
+                            self.preview_scroll = 0;
                         
                             // Only mark as changed if actually changed
-                            if old_selection != self.selected_search_index 
-                              && self.detail_view_mode == DetailViewMode::RelatedFiles {
+                            if old_selection != self.selected_search_index {
                                 self.last_selection_change = Instant::now();
                             }
                         }
                     }
+                    KeyCode::Down
+                        if self.selected_search_index + 1 >= self.search_results.len() =>
+                    {
+                        // Moving past the last result fetches the next batch
+                        self.load_more_results();
+                    }
                     KeyCode::Down => {
                         if self.selected_search_index + 1 < self.search_results.len() {
                             let old_selection = self.selected_search_index;
                             self.selected_search_index += 1;
+                            self.preview_scroll = 0;
                         
                             // Only mark as changed if actually changed
-                            if old_selection != self.selected_search_index 
-                              && self.detail_view_mode == DetailViewMode::RelatedFiles {
+                            if old_selection != self.selected_search_index {
                                 self.last_selection_change = Instant::now();
                             }
                         }
@@ -511,19 +3965,52 @@ impl App {
                         self.input_mode = InputMode::Normal;
                     }
                     KeyCode::Enter => {
-                        // Perform search and exit editing mode
+                        // Perform search, then continue as configured by `editing_enter`
+                        self.record_search_history();
                         self.perform_search();
-                        self.input_mode = InputMode::Normal;
+                        match self.settings.editing_enter {
+                            EditingEnter::Stay => {}
+                            EditingEnter::Normal => self.input_mode = InputMode::Normal,
+                            EditingEnter::Open => {
+                                self.input_mode = InputMode::Normal;
+                                self.open_top_result(terminal);
+                            }
+                        }
+                    }
+                    // Ctrl+L: search and open the top result straight away
+                    KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        if self.search_mode == SearchMode::Semantic {
+                            self.set_status(
+                                "Ctrl+L needs lexical search; semantic results come later",
+                            );
+                            return;
+                        }
+                        self.perform_search();
+                        if self.open_top_result(terminal) {
+                            self.input_mode = InputMode::Normal;
+                        } else {
+                            self.set_status("No match");
+                        }
                     }
+                    // Up/Down recall earlier queries once the query is empty or committed
+                    KeyCode::Up if self.can_browse_history() => self.recall_history(true),
+                    KeyCode::Down if self.history_cursor.is_some() => self.recall_history(false),
                     KeyCode::Char(c) => {
-                        // Add character to search query while in editing mode
+                        // Add character to search query while in editing mode.
+                        // Semantic search is too costly per keystroke and waits for Enter.
+                        self.history_cursor = None;
                         self.search_query.push(c);
-                        self.perform_search();
+                        if self.search_mode == SearchMode::Lexical {
+                            self.perform_search();
+                        }
                     }
                     KeyCode::Backspace => {
                         // Delete character from search query
+                        self.history_cursor = None;
                         self.search_query.pop();
-                        self.perform_search();
+                        if self.search_mode == SearchMode::Lexical {
+                            self.perform_search();
+                        }
                     }
                     _ => {}
                 }
@@ -531,6 +4018,120 @@ impl App {
         }
     }
 
+    /// Adds the query about to run to the search history, skipping a repeat of
+    /// the latest entry, and writes the history out.
+    fn record_search_history(&mut self) {
+        self.history_cursor = None;
+        let query = self.search_query.trim();
+        if query.is_empty() || self.search_history.last().map(String::as_str) == Some(query) {
+            return;
+        }
+        self.search_history.push(query.to_string());
+        let excess = self
+            .search_history
+            .len()
+            .saturating_sub(crate::app::settings::SEARCH_HISTORY_LIMIT);
+        self.search_history.drain(..excess);
+        if let Err(e) = crate::app::settings::save_search_history(&self.search_history) {
+            self.set_status(format!("Could not save search history: {}", e));
+        }
+    }
+
+    /// Whether Up should recall history rather than being ignored: while browsing
+    /// it already, or when nothing new has been typed since the last search.
+    fn can_browse_history(&self) -> bool {
+        self.history_cursor.is_some()
+            || self.search_query.is_empty()
+            || self.search_history.last() == Some(&self.search_query)
+    }
+
+    /// Steps to an older (`back`) or newer query in the history and runs it.
+    /// Stepping past the newest entry leaves an empty query.
+    fn recall_history(&mut self, back: bool) {
+        let len = self.search_history.len();
+        if len == 0 {
+            return;
+        }
+        let cursor = match (self.history_cursor, back) {
+            // The latest entry may already be on screen; start one before it
+            (None, true) if self.search_history.last() == Some(&self.search_query) => {
+                len.checked_sub(2)
+            }
+            (None, true) => Some(len - 1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            _ => None,
+        };
+        let Some(cursor) = cursor else {
+            if !back {
+                self.history_cursor = None;
+                self.search_query.clear();
+                self.run_search();
+            }
+            return;
+        };
+        self.history_cursor = Some(cursor);
+        self.search_query = self.search_history[cursor].clone();
+        if self.search_mode == SearchMode::Lexical {
+            self.perform_search();
+        }
+    }
+
+    /// Checks that `path` is still on disk before it is opened. A missing note is
+    /// dropped from the results and handled as `missing_note` says.
+    fn ensure_note_exists(&mut self, path: &str) -> bool {
+        if std::path::Path::new(path).exists() {
+            return true;
+        }
+        self.search_results.retain(|result| result.path != path);
+        self.selected_search_index = self
+            .selected_search_index
+            .min(self.search_results.len().saturating_sub(1));
+        let title = note_result(path, String::new()).title;
+        match self.settings.missing_note {
+            crate::app::settings::MissingNote::Ask => self.confirm(
+                format!("{} no longer exists. Rescan the vault?", title),
+                ConfirmAction::RescanVault,
+            ),
+            crate::app::settings::MissingNote::Remove => {
+                self.set_status(format!("{} no longer exists; removed from results", title));
+            }
+            crate::app::settings::MissingNote::Rescan => {
+                self.set_status(format!("{} no longer exists; rescanning", title));
+                self.start_scan();
+            }
+        }
+        false
+    }
+
+    /// Opens the first result of the query just run in the editor. Returns false
+    /// when there is none to open.
+    fn open_top_result(
+        &mut self,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    ) -> bool {
+        let top = match self.search_mode {
+            SearchMode::Lexical => self.search_results.first(),
+            // Still pending; the list shows the previous query's results
+            SearchMode::Semantic => None,
+        };
+        let Some(doc) = top else {
+            return false;
+        };
+        let path = doc.path.clone();
+        self.selected_search_index = 0;
+        if !self.ensure_note_exists(&path) {
+            return true;
+        }
+        let edited =
+            crate::config_editor::open_file_in_editor(terminal, &path, self.settings.read_only);
+        self.state = self.state_after_external(AppState::Search);
+        if edited.unwrap_or(false) {
+            self.reindex_after_edit();
+        }
+        true
+    }
+
     fn get_related_files_for_selected(&mut self) {
     // Don't do anything if we're already loading
     if self.is_loading_related_files {
@@ -544,17 +4145,99 @@ impl App {
 
     // If we have a selected search result, find related files for it
     if let Some(selected_result) = self.search_results.get(self.selected_search_index) {
-        // Create a channel to communicate results
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.related_files_receiver = Some(rx);
-
         // Clone the path to use in the thread
         let path = selected_result.path.clone();
+        self.current_related_document_path = Some(path.clone());
+
+        // Answer from the cache, or wait for a warm-up already looking this note up
+        if let Some(cached) = self.related_cache.get(&path) {
+            self.related_overlap = related_overlap(&path, cached);
+            self.related_files = cached.clone();
+            self.related_selected = 0;
+            self.is_loading_related_files = false;
+            self.sort_related_files();
+        } else if self.prewarming_path.as_deref() != Some(path.as_str()) {
+            self.spawn_related_lookup(path, false);
+        }
+    } else {
+        // No selected item, immediately clear loading state
+        self.is_loading_related_files = false;
+    }
+}
+
+    /// Abandons the related-files lookup in flight; its worker sees the bumped
+    /// generation and never sends its results.
+    fn cancel_related_lookup(&mut self) {
+        self.related_generation.fetch_add(1, Ordering::SeqCst);
+        self.is_loading_related_files = false;
+        self.current_related_document_path = None;
+    }
+
+    /// Starts looking up related files for the first result in the background,
+    /// so switching to the related view shows them straight away.
+    fn prewarm_related(&mut self) {
+        if !self.settings.prewarm_related || self.prewarming_path.is_some() {
+            return;
+        }
+        let Some(top) = self.search_results.first() else {
+            return;
+        };
+        if self.related_cache.contains_key(&top.path) {
+            return;
+        }
+        let path = top.path.clone();
+        self.prewarming_path = Some(path.clone());
+        self.spawn_related_lookup(path, true);
+    }
+
+    /// Finds notes similar to `path` on a worker thread and reports them as
+    /// `AppEvent::RelatedFiles`.
+    fn spawn_related_lookup(&self, path: String, prewarm: bool) {
+        // Results come back over the shared channel, tagged with the document path
+        let tx = self.event_tx.clone();
+        let attempts = self.settings.ai_max_attempts;
+        let source = self.settings.related_source;
+        let prefix_chars = self.settings.related_prefix_chars;
+        let score_kind = self.settings.embedding_score;
+        let current_generation = Arc::clone(&self.related_generation);
+        let generation = current_generation.load(Ordering::SeqCst);
 
         // Spawn a thread to handle the async operation
         std::thread::spawn(move || {
+            let _report = PanicReport::new(
+                &tx,
+                AppEvent::RelatedFiles {
+                    path: path.clone(),
+                    result: Err("Lookup thread terminated unexpectedly".to_string()),
+                    prewarm,
+                    generation,
+                },
+            );
             // Initialize runtime for async operations
             let rt = tokio::runtime::Runtime::new().unwrap();
+            let send = |result: Result<Vec<SearchResult>, String>| {
+                // A warm-up only fills the cache, so it is worth delivering even
+                // after the selection has moved on
+                if !prewarm && current_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let _ = tx.send(AppEvent::RelatedFiles {
+                    path: path.clone(),
+                    result,
+                    prewarm,
+                    generation,
+                });
+            };
+            let on_retry = |attempt: u32, max: u32| {
+                // Nobody is waiting on a warm-up, so it retries quietly
+                if prewarm {
+                    return;
+                }
+                let _ = tx.send(AppEvent::Status(format!(
+                    "Related files: retrying ({}/{})...",
+                    attempt, max
+                )));
+            };
 
             // Run the async process
             rt.block_on(async {
@@ -562,23 +4245,27 @@ impl App {
                 match notemancy_core::config::load_config() {
                     Ok(config) => {
                         // Create AI instance
-                        match notemancy_core::ai::AI::new(&config).await {
+                        let ai = with_retry(attempts, &on_retry, || notemancy_core::ai::AI::new(&config)).await;
+                        match log_ai_init("Related files", ai) {
                             Ok(ai) => {
                                 // First, read the content of the file to use for similarity search
                                 let content = match std::fs::read_to_string(&path) {
                                     Ok(content) => content,
                                     Err(e) => {
-                                        let _ = tx.send(Err(format!("Could not read file: {}", e)));
+                                        send(Err(format!("Could not read file: {}", e)));
                                         return;
                                     }
                                 };
+                                // Embed only the configured part of the note
+                                let title = note_result(&path, String::new()).title;
+                                let content = source.text_for(&title, &content, prefix_chars);
                                 
                                 // Use the content directly with find_similar_documents API
                                 // This ensures we're comparing based on content and not just paths
-                                match ai.find_similar_documents(&content, 20, None).await {
+                                match with_retry(attempts, &on_retry, || ai.find_similar_documents(&content, 20, None)).await {
                                     Ok(similar_docs) => {
                                         if similar_docs.is_empty() {
-                                            let _ = tx.send(Err("No similar documents found.".to_string()));
+                                            send(Err("No similar documents found.".to_string()));
                                             return;
                                         }
                                     
@@ -595,28 +4282,17 @@ impl App {
                                             if rel_path == &path {
                                                 continue;
                                             }
+
+                                            // Embeddings of deleted notes linger until the next full index
+                                            if !std::path::Path::new(rel_path).exists() {
+                                                continue;
+                                            }
                                         
-                                            // Extract title from path
-                                            let title = std::path::Path::new(rel_path)
-                                                .file_stem()
-                                                .and_then(|s| s.to_str())
-                                                .unwrap_or("")
-                                                .to_string();
-                                        
-                                            // Convert score (0 is best, 1 is worst in distance metrics)
-                                            // to a similarity percentage (100% is best, 0% is worst)
-                                            let similarity = (1.0 - score) * 100.0;
-                                        
-                                            results.push(notemancy_core::search::SearchResult {
-                                                path: rel_path.clone(),
-                                                title,
-                                                snippet: format!("Similarity: {:.1}%", similarity).into(),
-                                                score: 1.0 - score, // Higher score = better match in SearchResult
-                                            });
+                                            results.push(similarity_result(rel_path, score, score_kind));
                                         }
                                     
                                         if results.is_empty() {
-                                            let _ = tx.send(Err("No related documents found (current document excluded).".to_string()));
+                                            send(Err("No related documents found (current document excluded).".to_string()));
                                             return;
                                         }
                                     
@@ -627,31 +4303,42 @@ impl App {
                                         let top_results = results.into_iter().take(10).collect();
                                     
                                         // Send results
-                                        let _ = tx.send(Ok(top_results));
+                                        send(Ok(top_results));
                                     },
                                     Err(e) => {
-                                        let _ = tx.send(Err(format!("Error finding similar documents: {}", e)));
+                                        send(Err(format!("Error finding similar documents: {}", e)));
                                     }
                                 }
                             },
                             Err(e) => {
-                                let _ = tx.send(Err(format!("Error initializing AI: {}", e)));
+                                send(Err(format!("Error initializing AI: {}", e)));
                             }
                         }
                     },
                     Err(e) => {
-                        let _ = tx.send(Err(format!("Error loading config: {}", e)));
+                        send(Err(format!("Error loading config: {}", e)));
                     }
                 }
             });
         });
-    } else {
-        // No selected item, immediately clear loading state
-        self.is_loading_related_files = false;
     }
-}
 
     pub fn process(&mut self) {
+        self.refresh_preview_content();
+        self.cache_result_tags();
+
+        // The selection moved while a lookup was running; cancel it so the
+        // newly selected note is looked up straight away
+        if self.is_loading_related_files {
+            let selected = self
+                .search_results
+                .get(self.selected_search_index)
+                .map(|r| r.path.as_str());
+            if selected != self.current_related_document_path.as_deref() {
+                self.cancel_related_lookup();
+            }
+        }
+
     // Only do this for search mode in related files view
     if self.state == AppState::Search && 
        self.detail_view_mode == DetailViewMode::RelatedFiles && 
@@ -692,51 +4379,20 @@ impl App {
         }
     }
     
-    // Process any completed related files requests
-    self.process_related_files_receiver();
 }
 
 
 
-    pub fn process_related_files_receiver(&mut self) {
-        if let Some(ref rx) = self.related_files_receiver {
-            match rx.try_recv() {
-                Ok(result) => {
-                    match result {
-                        Ok(results) => {
-                            if results.is_empty() {
-                                self.related_files_error = Some("No related documents found that meet the similarity threshold.".to_string());
-                            } else {
-                                self.related_files = results;
-                                self.related_files_error = None;
-                            }
-                        }
-                        Err(error_msg) => {
-                            self.related_files_error = Some(error_msg);
-                        }
-                    }
-                    self.is_loading_related_files = false;
-                    self.related_files_receiver = None;
-                }
-                Err(std::sync::mpsc::TryRecvError::Empty) => {
-                    // Still waiting for results
-                }
-                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
-                    // Channel closed without sending results
-                    self.is_loading_related_files = false;
-                    self.related_files_receiver = None;
-                    self.related_files_error =
-                        Some("Failed to process related files request".to_string());
-                }
-            }
-        }
-    }
-
     fn draw(&mut self, frame: &mut ratatui::Frame) {
         let area = frame.area();
         match self.state {
             AppState::Starting => {
-                let paragraph = ratatui::widgets::Paragraph::new("notemancy is starting")
+                let spinner = self.spinner_chars[self.spinner_idx];
+                let text = format!(
+                    "Initializing search engine... {}\n\nPress Esc or q to quit.",
+                    spinner
+                );
+                let paragraph = ratatui::widgets::Paragraph::new(text)
                     .style(
                         Style::default()
                             .fg(Color::Rgb(224, 224, 224))
@@ -758,19 +4414,13 @@ impl App {
                 frame.render_widget(paragraph, area);
             }
             AppState::Indexing => {
-                let spinner = self.spinner_chars[self.spinner_idx];
-                let text = format!("Building search index... {}", spinner);
-                let paragraph = ratatui::widgets::Paragraph::new(text)
-                    .style(
-                        Style::default()
-                            .fg(Color::Rgb(224, 224, 224))
-                            .bg(Color::Rgb(22, 22, 22)),
-                    )
-                    .block(Block::default());
-                frame.render_widget(paragraph, area);
+                crate::app::ui::draw_indexing_ui(self, frame, area);
             }
             AppState::Preview => {
-                let text = "Hello, Ratatui!\n\nCreated using https://github.com/ratatui/templates\nPress Ctrl+S to search.\nPress Ctrl+P for commands.\nPress Esc, Ctrl-C or q to quit.";
+                let mut text = "Hello, Ratatui!\n\nCreated using https://github.com/ratatui/templates\nPress Ctrl+S to search.\nPress Ctrl+P for commands.\nPress Esc, Ctrl-C or q to quit.".to_string();
+                if let Some(status) = self.current_status() {
+                    text.push_str(&format!("\n\n{}", status));
+                }
                 let paragraph = ratatui::widgets::Paragraph::new(text)
                     .style(
                         Style::default()
@@ -790,10 +4440,205 @@ impl App {
             AppState::CommandPalette => {
                 draw_command_palette(self, frame, area);
             }
+            AppState::ConfigPreview => {
+                // Keep the search session visible underneath the overlay
+                if self.palette_return_state == AppState::Search {
+                    draw_search_ui(self, frame);
+                }
+                crate::app::ui::draw_config_preview(self, frame, area);
+            }
+        }
+        // Popups drawn below keep the plain background
+        if self.settings.state_tints {
+            if let Some(tint) = crate::app::ui::state_background(self.state) {
+                crate::app::ui::tint_background(frame, area, tint);
+            }
+        }
+        if self.text_prompt.is_some() {
+            crate::app::ui::draw_text_prompt(self, frame, area);
+        }
+        if self.confirmation.is_some() {
+            crate::app::ui::draw_confirmation(self, frame, area);
+        }
+        if self.saved_search_selected.is_some() {
+            crate::app::ui::draw_saved_searches(self, frame, area);
+        }
+        if self.tag_browser.is_some() {
+            crate::app::ui::draw_tag_browser(self, frame, area);
+        }
+        if self.benchmark_report.is_some() {
+            crate::app::ui::draw_benchmark_report(self, frame, area);
         }
     }
 
     fn quit(&mut self) {
         self.running = false;
+        if self.settings.startup_action == StartupAction::LastSession {
+            if let Err(e) = crate::app::settings::save_last_query(&self.search_query) {
+                crate::app::debug_log::log(format!("Could not save session: {}", e));
+            }
+        }
+    }
+}
+
+/// Whether `path` sits in a dot-prefixed folder, or is a dotfile, below `root`.
+fn is_hidden(root: &std::path::Path, path: &std::path::Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|component| match component {
+            std::path::Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+            _ => false,
+        })
+}
+
+/// The cutoff for "Modified Since": local midnight of a `YYYY-MM-DD` date, or
+/// a span of hours, days or weeks (`12h`, `7d`, `2w`) back from now.
+fn parse_since(input: &str) -> Option<std::time::SystemTime> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let midnight = date
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(chrono::Local)
+            .earliest()?;
+        return Some(midnight.into());
+    }
+    let unit = input.chars().last()?;
+    let amount: u64 = input[..input.len() - unit.len_utf8()].parse().ok()?;
+    let unit_secs = match unit {
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        _ => return None,
+    };
+    std::time::SystemTime::now().checked_sub(Duration::from_secs(amount.checked_mul(unit_secs)?))
+}
+
+/// Builds a `SearchResult` for a note path, titled after the file stem.
+pub fn note_result(path: &str, snippet: String) -> SearchResult {
+    // Extract title from path
+    let title = std::path::Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    SearchResult {
+        path: path.to_string(),
+        title,
+        snippet: snippet.into(),
+        score: 0.0,
+    }
+}
+
+/// Maps an embedding match onto a `SearchResult`, titled after the file stem.
+fn similarity_result(
+    rel_path: &str,
+    score: f32,
+    kind: crate::app::settings::EmbeddingScore,
+) -> SearchResult {
+    // Higher score = better match in SearchResult, shown as a percentage
+    let similarity = kind.similarity(score);
+
+    SearchResult {
+        score: similarity,
+        ..note_result(rel_path, format!("Similarity: {:.1}%", similarity * 100.0))
+    }
+}
+
+/// Runs an AI backend call, retrying failures with exponential backoff
+/// (500ms, 1s, 2s, ... capped at 32s) until `max_attempts` tries have been made.
+/// `on_retry` is told about every new attempt so workers can report it.
+async fn with_retry<T, E, F, Fut>(
+    max_attempts: u32,
+    on_retry: &impl Fn(u32, u32),
+    mut op: F,
+) -> std::result::Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, E>>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= max_attempts => return Err(e),
+            Err(_) => {
+                let backoff = 500u64.saturating_mul(1 << (attempt - 1).min(6));
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+                attempt += 1;
+                on_retry(attempt, max_attempts);
+            }
+        }
+    }
+}
+
+/// Logs whether a worker's AI backend came up, passing `result` through.
+fn log_ai_init<T, E: std::fmt::Display>(
+    worker: &str,
+    result: std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    match &result {
+        Ok(_) => crate::app::debug_log::log(format!("{}: AI backend initialized", worker)),
+        Err(e) => crate::app::debug_log::log(format!(
+            "{}: AI backend failed to initialize: {}",
+            worker, e
+        )),
+    }
+    result
+}
+
+/// Whether a full-text search for the note's file name returns the note. A name
+/// with nothing to search for can't be probed and counts as indexed.
+fn in_full_text_index(engine: &SearchEngine, path: &str) -> bool {
+    let stem = std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let query: String = stem
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    if query.trim().is_empty() {
+        return true;
     }
+    engine
+        .search(query.trim(), 50)
+        .is_ok_and(|results| results.iter().any(|result| same_note(&result.path, path)))
+}
+
+/// Whether a path an index reports, absolute or relative to the vault, is `path`.
+fn same_note(indexed: &str, path: &str) -> bool {
+    indexed == path || (!indexed.is_empty() && std::path::Path::new(path).ends_with(indexed))
+}
+
+/// Lowercased words of three or more letters plus `#tags`, used for lexical overlap.
+fn note_terms(content: &str) -> HashSet<String> {
+    content
+        .split(|c: char| !(c.is_alphanumeric() || c == '#' || c == '_' || c == '-'))
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard overlap of terms between the note at `source` and each related file.
+fn related_overlap(source: &str, related: &[SearchResult]) -> HashMap<String, f32> {
+    let Ok(content) = std::fs::read_to_string(source) else {
+        return HashMap::new();
+    };
+    let source_terms = note_terms(&content);
+    related
+        .iter()
+        .filter_map(|result| {
+            let terms = note_terms(&std::fs::read_to_string(&result.path).ok()?);
+            let union = source_terms.union(&terms).count();
+            let shared = source_terms.intersection(&terms).count();
+            let overlap = if union == 0 {
+                0.0
+            } else {
+                shared as f32 / union as f32
+            };
+            Some((result.path.clone(), overlap))
+        })
+        .collect()
 }
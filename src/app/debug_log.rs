@@ -0,0 +1,37 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+/// The open `log_file`, if any. Worker threads log too, hence the mutex.
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Starts appending to the log at `path`; `~/` is the home directory.
+pub fn open(path: &str) -> Result<(), String> {
+    let path = match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => std::path::PathBuf::from(path),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Could not open log {}: {}", path.display(), e))?;
+    if let Ok(mut log) = LOG_FILE.lock() {
+        *log = Some(file);
+    }
+    Ok(())
+}
+
+/// Appends a timestamped line to the log. Does nothing unless `open` succeeded.
+pub fn log(message: impl std::fmt::Display) {
+    let Ok(mut log) = LOG_FILE.lock() else {
+        return;
+    };
+    if let Some(ref mut file) = *log {
+        let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+        let _ = writeln!(file, "{} {}", now, message);
+    }
+}
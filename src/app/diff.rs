@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One line of a line-based diff.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// Largest LCS table `diff_lines` builds, in cells (about 32 MB).
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Diffs two texts line by line using a longest-common-subsequence table.
+/// Unchanged leading and trailing lines are matched first; when what is left
+/// is too big for the table, it is shown as removed and then added whole.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line.to_string()))
+        .collect();
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    if (old_middle.len() + 1).saturating_mul(new_middle.len() + 1) > MAX_DIFF_CELLS {
+        diff.extend(
+            old_middle
+                .iter()
+                .map(|line| DiffLine::Removed(line.to_string())),
+        );
+        diff.extend(
+            new_middle
+                .iter()
+                .map(|line| DiffLine::Added(line.to_string())),
+        );
+    } else {
+        diff.extend(lcs_diff(old_middle, new_middle));
+    }
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line.to_string())),
+    );
+    diff
+}
+
+fn lcs_diff(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    // lcs[i][j] is the LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    diff.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    diff
+}
+
+/// Where the snapshot of a note taken at the last vector index is kept, mirroring
+/// the note's path under e.g. `~/.cache/notemancy/snapshots/`.
+///
+/// notemancy_core doesn't expose the text it indexed, so these copies stand in for it.
+pub fn snapshot_path(note_path: &str) -> Option<PathBuf> {
+    let mut path = dirs::cache_dir()?.join("notemancy").join("snapshots");
+    for component in Path::new(note_path).components() {
+        if let Component::Normal(part) = component {
+            path.push(part);
+        }
+    }
+    Some(path)
+}
+
+/// Where the time each snapshot was taken is recorded, e.g.
+/// `~/.cache/notemancy/snapshots.json`. File times can't be trusted for this,
+/// since copying a note keeps its modification time on some platforms.
+fn indexed_times_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("notemancy").join("snapshots.json"))
+}
+
+/// When each note was last snapshotted by Index Vectors, keyed by note path.
+pub fn load_indexed_times() -> HashMap<String, SystemTime> {
+    let Some(contents) = indexed_times_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return HashMap::new();
+    };
+    let seconds: HashMap<String, u64> = serde_json::from_str(&contents).unwrap_or_default();
+    seconds
+        .into_iter()
+        .map(|(path, secs)| (path, UNIX_EPOCH + Duration::from_secs(secs)))
+        .collect()
+}
+
+fn save_indexed_times(times: &HashMap<String, SystemTime>) -> Result<(), String> {
+    let path = indexed_times_path().ok_or("No cache directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let seconds: HashMap<&String, u64> = times
+        .iter()
+        .map(|(note, time)| {
+            let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            (note, secs)
+        })
+        .collect();
+    let contents = serde_json::to_string(&seconds).map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Deletes the snapshots of notes that no longer exist and returns how many went.
+/// Snapshot paths drop the note path's root, so a note is looked for both from
+/// `/` and from the working directory before its snapshot counts as orphaned.
+pub fn prune_snapshots() -> Result<usize, String> {
+    let Some(root) = snapshot_path("") else {
+        return Err("No cache directory available".to_string());
+    };
+    let mut pruned = 0;
+    let mut pending = vec![root.clone()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&root) else {
+                continue;
+            };
+            if Path::new("/").join(relative).exists() || relative.exists() {
+                continue;
+            }
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+            pruned += 1;
+        }
+    }
+    let mut times = load_indexed_times();
+    let before = times.len();
+    times.retain(|note, _| Path::new(note).exists());
+    if times.len() != before {
+        save_indexed_times(&times)?;
+    }
+    Ok(pruned)
+}
+
+/// Copies the current content of every note into the snapshot cache and records
+/// when it was taken.
+pub fn save_snapshots(note_paths: &[String]) -> Result<usize, String> {
+    let mut times = load_indexed_times();
+    let now = SystemTime::now();
+    let mut saved = 0;
+    for note_path in note_paths {
+        let Some(snapshot) = snapshot_path(note_path) else {
+            return Err("No cache directory available".to_string());
+        };
+        if let Some(parent) = snapshot.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // Notes that vanished since the scan are simply skipped
+        if fs::copy(note_path, &snapshot).is_ok() {
+            times.insert(note_path.clone(), now);
+            saved += 1;
+        }
+    }
+    save_indexed_times(&times)?;
+    Ok(saved)
+}
+
+/// Reads the snapshot of a note, if one was taken.
+pub fn load_snapshot(note_path: &str) -> Option<String> {
+    fs::read_to_string(snapshot_path(note_path)?).ok()
+}
+
+/// Whether a note's embedding is current, judged by its snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexStatus {
+    /// No snapshot, so the note hasn't been through Index Vectors from this console.
+    /// It may still have been embedded through the CLI.
+    NoSnapshot,
+    /// Indexed at the given time and unchanged since.
+    Indexed(SystemTime),
+    /// Indexed at the given time, but the note has been modified after that.
+    Stale(SystemTime),
+}
+
+/// Vector-index status of a note last snapshotted at `indexed_at` (from
+/// `load_indexed_times`) and last modified at `modified`.
+pub fn index_status(indexed_at: Option<SystemTime>, modified: Option<SystemTime>) -> IndexStatus {
+    match (indexed_at, modified) {
+        (None, _) => IndexStatus::NoSnapshot,
+        (Some(indexed_at), Some(modified)) if modified > indexed_at => {
+            IndexStatus::Stale(indexed_at)
+        }
+        (Some(indexed_at), _) => IndexStatus::Indexed(indexed_at),
+    }
+}
@@ -0,0 +1,75 @@
+use notemancy_core::search::SearchResult;
+use serde::Serialize;
+
+/// File formats search results can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportedResults<'a> {
+    query: &'a str,
+    results: Vec<ExportedResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportedResult<'a> {
+    path: &'a str,
+    title: &'a str,
+    score: f32,
+}
+
+/// Serializes `results` with the query that produced them. CSV repeats the
+/// query in its first column so every row stands on its own.
+pub fn export_results(query: &str, results: &[SearchResult], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => {
+            let mut csv = String::from("query,path,title,score\n");
+            for result in results {
+                csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    csv_field(query),
+                    csv_field(&result.path),
+                    csv_field(&result.title),
+                    result.score
+                ));
+            }
+            csv
+        }
+        ExportFormat::Json => {
+            let exported = ExportedResults {
+                query,
+                results: results
+                    .iter()
+                    .map(|result| ExportedResult {
+                        path: &result.path,
+                        title: &result.title,
+                        score: result.score,
+                    })
+                    .collect(),
+            };
+            // Strings and floats always serialize (NaN becomes null)
+            serde_json::to_string_pretty(&exported).unwrap_or_default()
+        }
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
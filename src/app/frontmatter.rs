@@ -0,0 +1,49 @@
+use serde_yaml::Value;
+
+/// Parses the YAML frontmatter block (`---` ... `---`) at the top of a note.
+pub fn parse(content: &str) -> Option<Value> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let end = rest
+        .match_indices("\n---")
+        .map(|(i, _)| i)
+        .find(|&i| matches!(rest[i + 4..].chars().next(), None | Some('\n' | '\r')))?;
+    serde_yaml::from_str(&rest[..end]).ok()
+}
+
+/// The `tags` of a note, given either as a list or as a comma-separated string.
+pub fn tags(content: &str) -> Vec<String> {
+    let Some(frontmatter) = parse(content) else {
+        return Vec::new();
+    };
+    match frontmatter.get("tags") {
+        Some(Value::Sequence(tags)) => tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The first of `fields` that holds a number, read from the note's frontmatter.
+/// Numbers written as strings (`priority: "2"`) count too.
+pub fn numeric_field(content: &str, fields: &[String]) -> Option<f32> {
+    let frontmatter = parse(content)?;
+    fields
+        .iter()
+        .find_map(|field| match frontmatter.get(field.as_str())? {
+            Value::Number(n) => n.as_f64().map(|n| n as f32),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+}
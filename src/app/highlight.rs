@@ -1,6 +1,9 @@
+use crate::app::settings::HtmlMode;
 use once_cell::sync::Lazy;
-use pulldown_cmark::{CodeBlockKind, Event as MdEvent, Parser, Tag};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event as MdEvent, Options, Parser, Tag};
 use ratatui::style::Modifier;
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthChar;
 
 use ratatui::{
     style::{Color, Style},
@@ -13,110 +16,486 @@ use syntect::parsing::SyntaxSet;
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
 
-fn highlight_markdown(content: &str) -> Vec<ratatui::text::Line<'static>> {
-    use pulldown_cmark::{CodeBlockKind, Event as MdEvent, Parser, Tag};
-    let parser = Parser::new(content);
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-    let mut in_code_block = false;
-    let mut code_lang = String::new();
-    let mut code_buffer = String::new();
+/// Theme used when none is configured, or the configured one doesn't exist.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
 
-    for event in parser {
-        match event {
-            MdEvent::Start(tag) => match tag {
-                Tag::CodeBlock(info) => {
-                    // Flush any pending normal text.
-                    if !current_line.is_empty() {
-                        lines.push(ratatui::text::Line::from(current_line.clone()));
-                        current_line.clear();
-                    }
-                    in_code_block = true;
-                    match info {
-                        CodeBlockKind::Fenced(lang) => {
-                            code_lang = lang.to_string().to_owned();
-                        }
-                        CodeBlockKind::Indented => {
-                            code_lang.clear();
-                        }
-                    }
+/// Names of the bundled syntect themes, in alphabetical order.
+pub fn theme_names() -> Vec<String> {
+    THEME_SET.themes.keys().cloned().collect()
+}
+
+/// Highlights source code with syntect, picking the syntax from a token such as
+/// a code fence language or a file extension ("rust", "yaml", "toml", ...).
+pub fn highlight_code(code: &str, token: &str, theme: &str) -> Vec<Line<'static>> {
+    let syntax = SYNTAX_SET
+        .find_syntax_by_token(token)
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let theme = THEME_SET
+        .themes
+        .get(theme)
+        .unwrap_or(&THEME_SET.themes[DEFAULT_THEME]);
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    code.lines()
+        .map(|line| {
+            let ranges = highlighter.highlight(line, &SYNTAX_SET);
+            let spans: Vec<Span> = ranges
+                .into_iter()
+                .map(|(s, text)| {
+                    let fg = Color::Rgb(s.foreground.r, s.foreground.g, s.foreground.b);
+                    Span::styled(text.to_string(), Style::default().fg(fg))
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Renders a footnote number with Unicode superscript digits, e.g. `12` -> `"¹²"`.
+fn superscript(n: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    n.to_string()
+        .chars()
+        .map(|c| DIGITS[c.to_digit(10).unwrap_or(0) as usize])
+        .collect()
+}
+
+/// Returns the display number for a footnote label, numbering labels in order of first use.
+fn footnote_number(
+    label: &str,
+    numbers: &mut HashMap<String, usize>,
+    order: &mut Vec<String>,
+) -> usize {
+    if let Some(&n) = numbers.get(label) {
+        return n;
+    }
+    order.push(label.to_string());
+    let n = order.len();
+    numbers.insert(label.to_string(), n);
+    n
+}
+
+/// A heading found while rendering, with the index of its rendered line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: usize,
+    pub title: String,
+    pub line: usize,
+}
+
+/// Rendered preview lines together with the document outline.
+#[derive(Clone)]
+pub struct RenderedMarkdown {
+    pub lines: Vec<Line<'static>>,
+    pub headings: Vec<Heading>,
+    /// 1-based source line of each rendered line, `None` for generated lines.
+    pub source_lines: Vec<Option<usize>>,
+}
+
+/// Builds a GitHub-style anchor for a heading, e.g. `"Setup & Usage"` -> `"setup--usage"`.
+pub fn heading_slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds bare `http(s)://` URLs in plain text, returning their byte ranges.
+/// Trailing sentence punctuation is not considered part of the URL.
+pub fn find_bare_urls(text: &str) -> Vec<(usize, usize)> {
+    let mut urls = Vec::new();
+    let mut search_from = 0;
+    while search_from < text.len() {
+        let rest = &text[search_from..];
+        let Some(found) = [rest.find("https://"), rest.find("http://")]
+            .into_iter()
+            .flatten()
+            .min()
+        else {
+            break;
+        };
+        let start = search_from + found;
+        let end = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+            .map_or(text.len(), |len| start + len);
+        let url = text[start..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']);
+        let end = start + url.len();
+        // Skip a lone scheme such as "https://" followed by nothing useful
+        if !url.ends_with("://") {
+            urls.push((start, end));
+        }
+        search_from = end.max(start + 1);
+    }
+    urls
+}
+
+/// Collects every link target in a note: markdown links, autolinks and bare URLs.
+pub fn extract_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for event in Parser::new_ext(content, Options::ENABLE_FOOTNOTES) {
+        let found = match event {
+            MdEvent::Start(Tag::Link(_, dest, _)) if dest.starts_with("http") => {
+                vec![dest.to_string()]
+            }
+            MdEvent::Text(text) => find_bare_urls(&text)
+                .into_iter()
+                .map(|(start, end)| text[start..end].to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+        for url in found {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+/// Splits text into spans, styling any bare URLs as links.
+fn push_text_with_urls(spans: &mut Vec<Span<'static>>, text: &str, link_style: Style) {
+    let mut last = 0;
+    for (start, end) in find_bare_urls(text) {
+        if start > last {
+            spans.push(Span::raw(text[last..start].to_string()));
+        }
+        spans.push(Span::styled(text[start..end].to_string(), link_style));
+        last = end;
+    }
+    if last < text.len() {
+        spans.push(Span::raw(text[last..].to_string()));
+    }
+}
+
+/// Splits text into prose and math pieces, returning `(is_math, piece)` pairs.
+/// Math is `$$...$$` or `$...$` where the inner text doesn't start or end with a
+/// space and the closing `$` isn't followed by a digit, so prices like
+/// "$5 and $10" stay prose. Delimiters are kept in the math piece.
+pub fn split_math(text: &str) -> Vec<(bool, &str)> {
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+    while let Some(offset) = text[i..].find('$') {
+        let open = i + offset;
+        let delim = if text[open..].starts_with("$$") {
+            "$$"
+        } else {
+            "$"
+        };
+        let inner_start = open + delim.len();
+        let close = text[inner_start..].find(delim).map(|j| inner_start + j);
+        let valid = close.is_some_and(|close| {
+            let inner = &text[inner_start..close];
+            let after = text[close + delim.len()..].chars().next();
+            !inner.is_empty()
+                && !inner.starts_with(char::is_whitespace)
+                && !inner.ends_with(char::is_whitespace)
+                && !after.is_some_and(|c| c.is_ascii_digit())
+        });
+        match close {
+            Some(close) if valid => {
+                if open > last {
+                    pieces.push((false, &text[last..open]));
                 }
-                Tag::Heading(level, ..) => {
-                    // For headings, prepend the appropriate number of '#' characters.
-                    current_line.push_str(&"#".repeat(level as usize));
-                    current_line.push(' ');
+                let end = close + delim.len();
+                pieces.push((true, &text[open..end]));
+                last = end;
+                i = end;
+            }
+            _ => i = inner_start,
+        }
+    }
+    if last < text.len() {
+        pieces.push((false, &text[last..]));
+    }
+    pieces
+}
+
+/// Splits raw HTML into tags and the text between them,
+/// e.g. `a<b>c</b>` becomes `["a", "<b>", "c", "</b>"]`.
+fn split_html(html: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        if start > 0 {
+            pieces.push(&rest[..start]);
+        }
+        pieces.push(&rest[start..start + len + 1]);
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// Returns the lowercase name of an HTML tag and whether it closes an element.
+fn html_tag_name(tag: &str) -> (String, bool) {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let closing = inner.starts_with('/');
+    let name = inner
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    (name, closing)
+}
+
+/// How `render_markdown` treats the parts of a note that are a matter of taste.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions<'a> {
+    pub html_mode: HtmlMode,
+    pub theme: &'a str,
+    // Soft breaks become spaces instead of ending the line
+    pub join_soft_breaks: bool,
+    // Code lines wider than this are cut off with a `→`; `None` leaves them to wrap
+    pub code_width: Option<usize>,
+}
+
+impl Default for RenderOptions<'_> {
+    fn default() -> Self {
+        Self {
+            html_mode: HtmlMode::Strip,
+            theme: DEFAULT_THEME,
+            join_soft_breaks: true,
+            code_width: None,
+        }
+    }
+}
+
+/// Cuts `line` to `width` columns, replacing the last visible column with `→`
+/// when anything had to go.
+fn truncate_line(line: Line<'static>, width: usize) -> Line<'static> {
+    if line.width() <= width {
+        return line;
+    }
+    let mut remaining = width.saturating_sub(1);
+    let mut spans = Vec::new();
+    for span in line.spans {
+        if remaining == 0 {
+            break;
+        }
+        let mut content = String::new();
+        for c in span.content.chars() {
+            let w = c.width().unwrap_or(0);
+            if w > remaining {
+                remaining = 0;
+                break;
+            }
+            remaining -= w;
+            content.push(c);
+        }
+        spans.push(Span::styled(content, span.style));
+    }
+    spans.push(Span::styled(
+        "→",
+        Style::default().fg(Color::Rgb(120, 120, 120)),
+    ));
+    Line::from(spans)
+}
+
+/// A list item being rendered: its marker until the first line has used it, and
+/// the width continuation lines are indented by to align under the item's text.
+struct ListItemPrefix {
+    marker: Option<String>,
+    width: usize,
+}
+
+/// Prefixes a rendered line with the markers or indentation of the open list items.
+fn item_line(spans: Vec<Span<'static>>, items: &mut [ListItemPrefix]) -> Line<'static> {
+    if items.is_empty() {
+        return Line::from(spans);
+    }
+    let mut prefixed = Vec::with_capacity(spans.len() + items.len());
+    for item in items.iter_mut() {
+        match item.marker.take() {
+            Some(marker) => prefixed.push(Span::styled(
+                marker,
+                Style::default().fg(Color::Rgb(69, 137, 255)),
+            )),
+            None => prefixed.push(Span::raw(" ".repeat(item.width))),
+        }
+    }
+    prefixed.extend(spans);
+    Line::from(prefixed)
+}
+
+/// A table being rendered. Rows are held back until the table ends, so each
+/// column can be padded to its widest cell.
+struct TableBuffer {
+    alignments: Vec<Alignment>,
+    // Finished rows with the source line each came from
+    rows: Vec<(Vec<Vec<Span<'static>>>, usize)>,
+    // Cells of the row being rendered
+    row: Vec<Vec<Span<'static>>>,
+    row_source: usize,
+    // Leading rows that make up the header
+    head_rows: usize,
+}
+
+impl TableBuffer {
+    fn new(alignments: Vec<Alignment>) -> Self {
+        Self {
+            alignments,
+            rows: Vec::new(),
+            row: Vec::new(),
+            row_source: 0,
+            head_rows: 0,
+        }
+    }
+
+    fn end_row(&mut self) {
+        let row = std::mem::take(&mut self.row);
+        self.rows.push((row, self.row_source));
+    }
+
+    /// Lays the rows out with `│` between columns and a rule under the header.
+    fn render(self) -> Vec<(Line<'static>, Option<usize>)> {
+        let border = Style::default().fg(Color::Rgb(110, 110, 110));
+        let columns = self
+            .rows
+            .iter()
+            .map(|(cells, _)| cells.len())
+            .max()
+            .unwrap_or(0);
+        let mut widths = vec![0; columns];
+        for (cells, _) in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.iter().map(Span::width).sum());
+            }
+        }
+        let mut lines = Vec::new();
+        for (n, (mut cells, source)) in self.rows.into_iter().enumerate() {
+            if n > 0 && n == self.head_rows {
+                let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+                lines.push((Line::from(Span::styled(rule.join("─┼─"), border)), None));
+            }
+            let mut spans = Vec::new();
+            for (i, &width) in widths.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(" │ ", border));
                 }
-                _ => {}
-            },
-            MdEvent::End(tag) => match tag {
-                Tag::CodeBlock(_) => {
-                    // Process the accumulated code block.
-                    let syntax = SYNTAX_SET
-                        .find_syntax_by_token(code_lang.as_str())
-                        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-                    let theme = &THEME_SET.themes["base16-ocean.dark"];
-                    let mut highlighter = HighlightLines::new(syntax, theme);
-                    let code_lines: Vec<&str> = code_buffer.lines().collect();
-                    for line in code_lines {
-                        let ranges = highlighter.highlight(line, &SYNTAX_SET);
-                        let spans: Vec<ratatui::text::Span> = ranges
-                            .into_iter()
-                            .map(|(s, text)| {
-                                let fg = Color::Rgb(s.foreground.r, s.foreground.g, s.foreground.b);
-                                ratatui::text::Span::styled(
-                                    text.to_string(),
-                                    Style::default().fg(fg),
-                                )
-                            })
-                            .collect();
-                        lines.push(ratatui::text::Line::from(spans));
+                let mut cell = cells.get_mut(i).map(std::mem::take).unwrap_or_default();
+                if n < self.head_rows {
+                    for span in &mut cell {
+                        span.style = span.style.add_modifier(Modifier::BOLD);
                     }
-                    code_buffer.clear();
-                    in_code_block = false;
                 }
-                Tag::Heading(..) => {
-                    // End of a heading: flush the current line.
-                    lines.push(ratatui::text::Line::from(current_line.clone()));
-                    current_line.clear();
+                let gap = width - cell.iter().map(Span::width).sum::<usize>();
+                let (before, after) = match self.alignments.get(i) {
+                    Some(Alignment::Right) => (gap, 0),
+                    Some(Alignment::Center) => (gap / 2, gap - gap / 2),
+                    _ => (0, gap),
+                };
+                if before > 0 {
+                    spans.push(Span::raw(" ".repeat(before)));
                 }
-                _ => {}
-            },
-            MdEvent::Text(text) => {
-                if in_code_block {
-                    code_buffer.push_str(&text);
-                } else {
-                    current_line.push_str(&text);
+                spans.extend(cell);
+                // The last column isn't padded out to the edge
+                if after > 0 && i + 1 < columns {
+                    spans.push(Span::raw(" ".repeat(after)));
                 }
             }
-            MdEvent::SoftBreak | MdEvent::HardBreak => {
-                if in_code_block {
-                    code_buffer.push('\n');
-                } else {
-                    current_line.push('\n');
-                    lines.push(ratatui::text::Line::from(current_line.clone()));
-                    current_line.clear();
-                }
-            }
-            _ => {}
+            lines.push((Line::from(spans), Some(source)));
         }
+        lines
     }
-    if !current_line.is_empty() {
-        lines.push(ratatui::text::Line::from(current_line));
-    }
-    lines
 }
 
 pub fn highlight_full_markdown(content: &str) -> Vec<Line<'static>> {
-    let parser = Parser::new(content);
-    let mut lines = Vec::new();
+    render_markdown(content, RenderOptions::default()).lines
+}
+
+pub fn render_markdown(content: &str, options: RenderOptions) -> RenderedMarkdown {
+    let RenderOptions {
+        html_mode,
+        theme,
+        join_soft_breaks,
+        code_width,
+    } = options;
+    // pulldown-cmark may split text around brackets it tried to parse as a link;
+    // rejoin adjacent text so `[[wikilinks]]` and inline math arrive in one piece
+    let mut events: Vec<(MdEvent, std::ops::Range<usize>)> = Vec::new();
+    let parser_options = Options::ENABLE_FOOTNOTES | Options::ENABLE_TABLES;
+    for (event, range) in Parser::new_ext(content, parser_options).into_offset_iter() {
+        if let (MdEvent::Text(text), Some((MdEvent::Text(previous), previous_range))) =
+            (&event, events.last_mut())
+        {
+            *previous = format!("{}{}", previous, text).into();
+            previous_range.end = range.end;
+            continue;
+        }
+        events.push((event, range));
+    }
+    // Each rendered line is paired with the 1-based source line it came from
+    let mut lines: Vec<(Line<'static>, Option<usize>)> = Vec::new();
     let mut current_spans = Vec::new();
 
+    // Source line tracking for the line being built and the current code block
+    let newline_offsets: Vec<usize> = content.match_indices('\n').map(|(i, _)| i).collect();
+    let line_of = |offset: usize| newline_offsets.partition_point(|&nl| nl < offset) + 1;
+    let mut span_source: Option<usize> = None;
+    let mut code_source = 0;
+
+    // Outline tracking: the level and text of the heading being rendered
+    let mut headings = Vec::new();
+    let mut current_heading: Option<(usize, String)> = None;
+
     // For code block processing
     let mut in_code_block = false;
     let mut code_lang = String::new();
     let mut code_buffer = String::new();
 
-    for event in parser {
+    // Footnotes are numbered by first reference and collected for a section at the end.
+    // Definitions render into their own buffer so they can appear out of order in the source.
+    let mut footnote_numbers: HashMap<String, usize> = HashMap::new();
+    let mut footnote_order: Vec<String> = Vec::new();
+    let mut footnote_defs: HashMap<String, Vec<(Line<'static>, Option<usize>)>> = HashMap::new();
+    let mut body_lines: Vec<(Line<'static>, Option<usize>)> = Vec::new();
+    let footnote_style = Style::default().fg(Color::Rgb(69, 137, 255));
+
+    // Link text (markdown links and autolinks) and bare URLs share one style
+    let mut in_link = false;
+    let link_style = Style::default()
+        .fg(Color::Rgb(69, 137, 255))
+        .add_modifier(Modifier::UNDERLINED);
+
+    // Raw HTML: dimmed source in `Raw` mode, formatting from open tags in `Render` mode
+    let html_style = Style::default().fg(Color::Rgb(110, 110, 110));
+    let mut html_modifier = Modifier::empty();
+
+    // Math can't be typeset in a terminal, so `$...$` and `$$...$$` are just set apart
+    let math_style = Style::default()
+        .fg(Color::Rgb(242, 166, 92))
+        .bg(Color::Rgb(48, 48, 48));
+    let mut in_math_block = false;
+
+    // Span count right after the last soft break that was joined with a space
+    let mut joined_break: Option<usize> = None;
+
+    // Open lists (the next number of ordered ones) and the list items being rendered
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut list_items: Vec<ListItemPrefix> = Vec::new();
+
+    // Wikilinks aren't markdown links, so they get their own accent
+    let wikilink_style = Style::default().fg(Color::Rgb(187, 134, 252));
+
+    // The table being rendered; cell text is built in `current_spans` as usual
+    let mut table: Option<TableBuffer> = None;
+
+    for (event, range) in events {
+        if current_spans.is_empty() {
+            span_source = Some(line_of(range.start));
+        }
         match event {
             MdEvent::Start(tag) => match tag {
                 Tag::CodeBlock(info) => {
@@ -124,13 +503,17 @@ pub fn highlight_full_markdown(content: &str) -> Vec<Line<'static>> {
                     match info {
                         CodeBlockKind::Fenced(lang) => {
                             code_lang = lang.to_string().to_owned();
+                            // Code starts on the line after the opening fence
+                            code_source = line_of(range.start) + 1;
                         }
                         CodeBlockKind::Indented => {
                             code_lang.clear();
+                            code_source = line_of(range.start);
                         }
                     }
                 }
                 Tag::Heading(level, ..) => {
+                    current_heading = Some((level as usize, String::new()));
                     // Prepend heading markers styled in blue and bold.
                     let markers = format!("{} ", "#".repeat(level as usize));
                     current_spans.push(Span::styled(
@@ -140,68 +523,270 @@ pub fn highlight_full_markdown(content: &str) -> Vec<Line<'static>> {
                             .add_modifier(Modifier::BOLD),
                     ));
                 }
-                Tag::List(_) => {
-                    // Prepend a bullet for list items.
-                    current_spans.push(Span::styled(
-                        "• ",
-                        Style::default().fg(Color::Rgb(69, 137, 255)),
-                    ));
+                Tag::List(first) => {
+                    // A nested list starts below the text of its parent item
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    lists.push(first);
+                }
+                Tag::Item => {
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    let marker = match lists.last_mut() {
+                        Some(Some(n)) => {
+                            *n += 1;
+                            format!("{}. ", *n - 1)
+                        }
+                        _ => "• ".to_string(),
+                    };
+                    list_items.push(ListItemPrefix {
+                        width: marker.chars().count(),
+                        marker: Some(marker),
+                    });
+                }
+                Tag::Link(..) => {
+                    in_link = true;
+                }
+                Tag::Table(alignments) => {
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    table = Some(TableBuffer::new(alignments));
+                }
+                Tag::TableHead | Tag::TableRow => {
+                    if let Some(ref mut table) = table {
+                        table.row_source = line_of(range.start);
+                    }
+                }
+                Tag::FootnoteDefinition(_) => {
+                    // Render the definition into a fresh buffer, parking the body lines.
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    body_lines = std::mem::take(&mut lines);
                 }
                 // You can add additional styling for Emphasis, Strong, etc. here.
                 _ => {}
             },
             MdEvent::End(tag) => match tag {
                 Tag::CodeBlock(_) => {
-                    // Process code block using your existing syntect approach.
-                    use once_cell::sync::Lazy;
-                    use syntect::easy::HighlightLines;
-                    use syntect::highlighting::ThemeSet;
-                    use syntect::parsing::SyntaxSet;
-                    static SYNTAX_SET: Lazy<SyntaxSet> =
-                        Lazy::new(SyntaxSet::load_defaults_newlines);
-                    static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
-                    let syntax = SYNTAX_SET
-                        .find_syntax_by_token(code_lang.as_str())
-                        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-                    let theme = &THEME_SET.themes["base16-ocean.dark"];
-                    let mut highlighter = HighlightLines::new(syntax, theme);
                     // Process each line in the code block.
-                    for line in code_buffer.lines() {
-                        let ranges = highlighter.highlight(line, &SYNTAX_SET);
-                        let spans: Vec<Span> = ranges
-                            .into_iter()
-                            .map(|(s, text)| {
-                                let fg = Color::Rgb(s.foreground.r, s.foreground.g, s.foreground.b);
-                                Span::styled(text.to_string(), Style::default().fg(fg))
-                            })
-                            .collect();
-                        lines.push(Line::from(spans));
+                    for (i, line) in highlight_code(&code_buffer, &code_lang, theme)
+                        .into_iter()
+                        .enumerate()
+                    {
+                        let line = match code_width {
+                            Some(width) => {
+                                let indent: usize = list_items.iter().map(|item| item.width).sum();
+                                truncate_line(line, width.saturating_sub(indent))
+                            }
+                            None => line,
+                        };
+                        lines.push((
+                            item_line(line.spans, &mut list_items),
+                            Some(code_source + i),
+                        ));
                     }
                     code_buffer.clear();
                     in_code_block = false;
                 }
-                Tag::Heading(..) | Tag::List(_) | Tag::Paragraph => {
+                Tag::Item => {
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    // An empty item still shows its marker
+                    if list_items.last().is_some_and(|item| item.marker.is_some()) {
+                        lines.push((item_line(Vec::new(), &mut list_items), span_source));
+                    }
+                    list_items.pop();
+                }
+                Tag::List(_) => {
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                    lists.pop();
+                }
+                Tag::Heading(..) | Tag::Paragraph => {
+                    if let Some((level, title)) = current_heading.take() {
+                        headings.push(Heading {
+                            level,
+                            title,
+                            line: lines.len(),
+                        });
+                    }
                     // End of a block: flush current spans as a new line.
                     if !current_spans.is_empty() {
-                        lines.push(Line::from(current_spans));
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                }
+                Tag::Link(..) => {
+                    in_link = false;
+                }
+                Tag::TableCell => {
+                    if let Some(ref mut table) = table {
+                        table.row.push(std::mem::take(&mut current_spans));
+                    }
+                }
+                // pulldown-cmark 0.9 puts header cells straight in `TableHead`
+                Tag::TableHead => {
+                    if let Some(ref mut table) = table {
+                        table.end_row();
+                        table.head_rows = table.rows.len();
+                    }
+                }
+                Tag::TableRow => {
+                    if let Some(ref mut table) = table {
+                        table.end_row();
+                    }
+                }
+                Tag::Table(_) => {
+                    let rendered = table.take().map(TableBuffer::render).unwrap_or_default();
+                    for (line, source) in rendered {
+                        lines.push((item_line(line.spans, &mut list_items), source));
+                    }
+                }
+                Tag::FootnoteDefinition(label) => {
+                    if !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
                         current_spans = Vec::new();
                     }
+                    let definition = std::mem::replace(&mut lines, std::mem::take(&mut body_lines));
+                    footnote_defs.insert(label.to_string(), definition);
                 }
                 _ => {}
             },
+            MdEvent::FootnoteReference(label) => {
+                let n = footnote_number(&label, &mut footnote_numbers, &mut footnote_order);
+                current_spans.push(Span::styled(superscript(n), footnote_style));
+            }
             MdEvent::Text(text) => {
+                if let Some((_, ref mut title)) = current_heading {
+                    title.push_str(&text);
+                }
+                // A definition joined onto its term's line still goes on a line of its own
+                if text.starts_with(": ")
+                    && joined_break == Some(current_spans.len())
+                    && current_spans.last().is_some_and(|span| span.content == " ")
+                {
+                    current_spans.pop();
+                    lines.push((item_line(current_spans, &mut list_items), span_source));
+                    current_spans = Vec::new();
+                    span_source = Some(line_of(range.start));
+                }
                 if in_code_block {
                     code_buffer.push_str(&text);
+                } else if current_spans.is_empty() && text.starts_with(": ") {
+                    // Definition list entry (`Term` followed by `: definition`):
+                    // indent the definition under its term.
+                    current_spans.push(Span::styled(
+                        "    │ ",
+                        Style::default().fg(Color::Rgb(150, 150, 150)),
+                    ));
+                    current_spans.push(Span::raw(text[2..].to_string()));
+                } else if in_link {
+                    current_spans.push(Span::styled(text.to_string(), link_style));
+                } else if text.trim() == "$$" {
+                    // A `$$` line opens or closes a display math block
+                    in_math_block = !in_math_block;
+                    current_spans.push(Span::styled(text.to_string(), math_style));
+                } else if in_math_block {
+                    current_spans.push(Span::styled(text.to_string(), math_style));
                 } else {
-                    current_spans.push(Span::raw(text.to_string()));
+                    let start = current_spans.len();
+                    for (is_link, piece) in crate::app::links::split_wikilinks(&text) {
+                        if is_link {
+                            let label = crate::app::links::wikilink_label(piece);
+                            current_spans.push(Span::styled(label.to_string(), wikilink_style));
+                            continue;
+                        }
+                        for (is_math, piece) in split_math(piece) {
+                            if is_math {
+                                current_spans.push(Span::styled(piece.to_string(), math_style));
+                            } else {
+                                push_text_with_urls(&mut current_spans, piece, link_style);
+                            }
+                        }
+                    }
+                    for span in &mut current_spans[start..] {
+                        span.style = span.style.add_modifier(html_modifier);
+                    }
+                }
+            }
+            // pulldown-cmark 0.9 reports both block and inline HTML as `Html`;
+            // block HTML arrives one source line at a time, newline included.
+            MdEvent::Html(html) => match html_mode {
+                HtmlMode::Strip => {}
+                HtmlMode::Raw => {
+                    let mut parts = html.split('\n').peekable();
+                    while let Some(part) = parts.next() {
+                        if !part.is_empty() {
+                            current_spans.push(Span::styled(part.to_string(), html_style));
+                        }
+                        if parts.peek().is_some() && !current_spans.is_empty() {
+                            lines.push((item_line(current_spans, &mut list_items), span_source));
+                            current_spans = Vec::new();
+                        }
+                    }
                 }
+                HtmlMode::Render => {
+                    for piece in split_html(&html) {
+                        if piece.starts_with('<') {
+                            let (name, closing) = html_tag_name(piece);
+                            let modifier = match name.as_str() {
+                                "b" | "strong" => Modifier::BOLD,
+                                "i" | "em" => Modifier::ITALIC,
+                                "u" => Modifier::UNDERLINED,
+                                "br" => {
+                                    lines.push((
+                                        item_line(current_spans, &mut list_items),
+                                        span_source,
+                                    ));
+                                    current_spans = Vec::new();
+                                    continue;
+                                }
+                                _ => continue,
+                            };
+                            if closing {
+                                html_modifier.remove(modifier);
+                            } else {
+                                html_modifier.insert(modifier);
+                            }
+                        } else {
+                            let text = piece.trim_end_matches('\n');
+                            if !text.is_empty() {
+                                current_spans.push(Span::styled(
+                                    text.to_string(),
+                                    Style::default().add_modifier(html_modifier),
+                                ));
+                            }
+                        }
+                    }
+                    // Block HTML ends its line
+                    if html.ends_with('\n') && !current_spans.is_empty() {
+                        lines.push((item_line(current_spans, &mut list_items), span_source));
+                        current_spans = Vec::new();
+                    }
+                }
+            },
+            MdEvent::SoftBreak if join_soft_breaks && !in_code_block => {
+                current_spans.push(Span::raw(" "));
+                joined_break = Some(current_spans.len());
             }
             MdEvent::SoftBreak | MdEvent::HardBreak => {
                 if in_code_block {
                     code_buffer.push('\n');
                 } else {
                     // End the current line.
-                    lines.push(Line::from(current_spans));
+                    lines.push((item_line(current_spans, &mut list_items), span_source));
                     current_spans = Vec::new();
                 }
             }
@@ -209,28 +794,163 @@ pub fn highlight_full_markdown(content: &str) -> Vec<Line<'static>> {
         }
     }
     if !current_spans.is_empty() {
-        lines.push(Line::from(current_spans));
+        lines.push((item_line(current_spans, &mut list_items), span_source));
+    }
+
+    // Definitions that were never referenced still get a number, after the referenced ones.
+    let mut unreferenced: Vec<&String> = footnote_defs
+        .keys()
+        .filter(|label| !footnote_numbers.contains_key(*label))
+        .collect();
+    unreferenced.sort();
+    for label in unreferenced.into_iter().cloned().collect::<Vec<_>>() {
+        footnote_number(&label, &mut footnote_numbers, &mut footnote_order);
+    }
+
+    if !footnote_order.is_empty() {
+        lines.push((Line::from(""), None));
+        lines.push((
+            Line::from(Span::styled(
+                "─── Footnotes ───",
+                Style::default().fg(Color::Rgb(150, 150, 150)),
+            )),
+            None,
+        ));
+        for (i, label) in footnote_order.iter().enumerate() {
+            let marker = Span::styled(format!("{} ", superscript(i + 1)), footnote_style);
+            match footnote_defs.remove(label) {
+                Some(definition) if !definition.is_empty() => {
+                    for (j, (line, source)) in definition.into_iter().enumerate() {
+                        let mut spans = vec![if j == 0 {
+                            marker.clone()
+                        } else {
+                            Span::raw("  ")
+                        }];
+                        spans.extend(line.spans);
+                        lines.push((Line::from(spans), source));
+                    }
+                }
+                _ => {
+                    lines.push((
+                        Line::from(vec![
+                            marker,
+                            Span::styled(
+                                format!("missing definition for [^{}]", label),
+                                Style::default().fg(Color::Rgb(150, 150, 150)),
+                            ),
+                        ]),
+                        None,
+                    ));
+                }
+            }
+        }
+    }
+    let (lines, source_lines) = lines.into_iter().unzip();
+    RenderedMarkdown {
+        lines,
+        headings,
+        source_lines,
+    }
+}
+
+/// Finds case-insensitive occurrences of `query` in `text` as byte ranges into
+/// `text`. Characters are compared one by one, so ranges always fall on char boundaries.
+pub fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query.chars().collect();
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+    let mut next = 0;
+    for (start, _) in text.char_indices() {
+        if start < next {
+            continue;
+        }
+        let mut end = start;
+        let mut candidate = text[start..].chars();
+        let is_match = query.iter().all(|q| match candidate.next() {
+            Some(c) if c.to_lowercase().eq(q.to_lowercase()) => {
+                end += c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if is_match {
+            matches.push((start, end));
+            next = end;
+        }
+    }
+    matches
+}
+
+/// Builds a snippet of up to `max_fragments` pieces of `content` around matches of
+/// `query`, each with `context_chars` characters on either side, joined with `…`.
+pub fn extract_snippet(
+    content: &str,
+    query: &str,
+    context_chars: usize,
+    max_fragments: usize,
+) -> Option<String> {
+    // Snippets are shown on one line, so collapse newlines and runs of spaces
+    let text = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut fragments: Vec<String> = Vec::new();
+    let mut covered = 0;
+    for (start, end) in find_matches(&text, query) {
+        if fragments.len() >= max_fragments {
+            break;
+        }
+        // Matches inside the previous fragment's context are already shown
+        if start < covered {
+            continue;
+        }
+        let from = match context_chars {
+            0 => start,
+            n => text[..start]
+                .char_indices()
+                .rev()
+                .nth(n - 1)
+                .map_or(0, |(i, _)| i),
+        }
+        .max(covered);
+        let to = text[end..]
+            .char_indices()
+            .nth(context_chars)
+            .map_or(text.len(), |(i, _)| end + i);
+        fragments.push(text[from..to].trim().to_string());
+        covered = to;
+    }
+    if fragments.is_empty() {
+        return None;
+    }
+    let mut snippet = fragments.join(" … ");
+    if !text.starts_with(&fragments[0]) {
+        snippet.insert_str(0, "… ");
+    }
+    if covered < text.len() {
+        snippet.push_str(" …");
     }
-    lines
+    Some(snippet)
 }
 
 pub fn highlight_matches(line: &Line, query: &str) -> Line<'static> {
     let mut new_spans = Vec::new();
     for span in &line.spans {
-        let text = span.content.to_string();
+        let text: &str = &span.content;
+        // Ranges come from the original text, so slicing stays on char boundaries
+        // even when lowercasing would change byte lengths.
         let mut start = 0;
-        let text_lower = text.to_lowercase();
-        let query_lower = query.to_lowercase();
-        while let Some(pos) = text_lower[start..].find(&query_lower) {
-            let pos = start + pos;
-            if pos > start {
-                new_spans.push(Span::styled(text[start..pos].to_string(), span.style));
+        for (match_start, match_end) in find_matches(text, query) {
+            if match_start > start {
+                new_spans.push(Span::styled(
+                    text[start..match_start].to_string(),
+                    span.style,
+                ));
             }
             new_spans.push(Span::styled(
-                text[pos..pos + query.len()].to_string(),
+                text[match_start..match_end].to_string(),
                 Style::default().fg(Color::Black).bg(Color::Yellow),
             ));
-            start = pos + query.len();
+            start = match_end;
         }
         if start < text.len() {
             new_spans.push(Span::styled(text[start..].to_string(), span.style));
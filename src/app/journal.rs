@@ -0,0 +1,53 @@
+use chrono::{DateTime, Local};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Expands a daily note pattern such as `journal/{YYYY-MM-DD}.md` for `now`.
+/// Relative patterns are taken from `root`; `~/` is the home directory.
+pub fn daily_note_path(pattern: &str, root: &Path, now: &DateTime<Local>) -> PathBuf {
+    let expanded = pattern
+        .replace("{YYYY-MM-DD}", &now.format("%Y-%m-%d").to_string())
+        .replace("{YYYY}", &now.format("%Y").to_string())
+        .replace("{MM}", &now.format("%m").to_string())
+        .replace("{DD}", &now.format("%d").to_string());
+    if let Some(rest) = expanded.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = PathBuf::from(expanded);
+    if path.is_absolute() {
+        path
+    } else {
+        root.join(path)
+    }
+}
+
+/// Appends a timestamped list entry to the daily note, creating the note
+/// (with a date heading) and its folder when they don't exist yet.
+pub fn append_entry(path: &Path, text: &str, now: &DateTime<Local>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let is_new = !path.exists();
+    // Start on a fresh line if the note doesn't end with one
+    let needs_newline =
+        fs::read(path).is_ok_and(|bytes| !bytes.is_empty() && !bytes.ends_with(b"\n"));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+
+    let mut entry = String::new();
+    if needs_newline {
+        entry.push('\n');
+    }
+    if is_new {
+        entry.push_str(&format!("# {}\n\n", now.format("%Y-%m-%d")));
+    }
+    entry.push_str(&format!("- {} {}\n", now.format("%H:%M"), text));
+    file.write_all(entry.as_bytes())
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
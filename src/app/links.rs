@@ -0,0 +1,220 @@
+use pulldown_cmark::{Event as MdEvent, Parser, Tag};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// A link target as written in a note, before resolving it to a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// `[[Note Title]]` or `[[Note Title|alias]]`, matched against file stems.
+    Wiki(String),
+    /// `[text](relative/path.md)`, resolved relative to the linking note.
+    Path(String),
+}
+
+/// Extracts the targets of `[[wikilinks]]` from text, dropping aliases and anchors.
+pub fn wikilink_targets(text: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let inner = &after[..end];
+        let target = inner.split(['|', '#']).next().unwrap_or("").trim();
+        if !target.is_empty() {
+            targets.push(target.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    targets
+}
+
+/// Splits text into plain pieces and `[[wikilink]]` pieces. Link pieces are the
+/// text between the brackets, e.g. `Note Title|alias`.
+pub fn split_wikilinks(text: &str) -> Vec<(bool, &str)> {
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    while let Some(start) = text[last..].find("[[").map(|i| last + i) {
+        let inner_start = start + 2;
+        let Some(end) = text[inner_start..].find("]]").map(|i| inner_start + i) else {
+            break;
+        };
+        let inner = &text[inner_start..end];
+        if inner.trim().is_empty() || inner.contains('\n') {
+            pieces.push((false, &text[last..inner_start]));
+            last = inner_start;
+            continue;
+        }
+        if start > last {
+            pieces.push((false, &text[last..start]));
+        }
+        pieces.push((true, inner));
+        last = end + 2;
+    }
+    if last < text.len() {
+        pieces.push((false, &text[last..]));
+    }
+    pieces
+}
+
+/// What a wikilink shows: its alias if it has one, otherwise the target.
+pub fn wikilink_label(inner: &str) -> &str {
+    match inner.split_once('|') {
+        Some((_, alias)) if !alias.trim().is_empty() => alias.trim(),
+        _ => inner.trim(),
+    }
+}
+
+/// Collects the outgoing note links of a markdown document.
+pub fn outgoing_links(content: &str) -> Vec<LinkTarget> {
+    let mut links = Vec::new();
+    for event in Parser::new(content) {
+        match event {
+            MdEvent::Start(Tag::Link(_, dest, _)) => {
+                let dest = dest.split('#').next().unwrap_or("");
+                let is_external = dest.contains("://") || dest.starts_with("mailto:");
+                if !is_external && dest.ends_with(".md") {
+                    links.push(LinkTarget::Path(dest.to_string()));
+                }
+            }
+            MdEvent::Text(text) => {
+                links.extend(wikilink_targets(&text).into_iter().map(LinkTarget::Wiki));
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+/// Resolves `..` and `.` components without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Maps link targets onto the notes of a vault.
+pub struct LinkResolver {
+    by_stem: HashMap<String, String>,
+    by_path: HashMap<PathBuf, String>,
+}
+
+impl LinkResolver {
+    pub fn new(paths: &[String]) -> Self {
+        let mut by_stem = HashMap::new();
+        let mut by_path = HashMap::new();
+        for path in paths {
+            if let Some(stem) = Path::new(path).file_stem().and_then(|s| s.to_str()) {
+                by_stem
+                    .entry(stem.to_lowercase())
+                    .or_insert_with(|| path.clone());
+            }
+            by_path.insert(normalize_path(Path::new(path)), path.clone());
+        }
+        Self { by_stem, by_path }
+    }
+
+    /// Returns the note path a link points to, if it exists in the vault.
+    pub fn resolve(&self, target: &LinkTarget, from: &str) -> Option<String> {
+        match target {
+            LinkTarget::Wiki(title) => {
+                // `[[folder/Note]]` resolves by its last segment
+                let stem = title.rsplit('/').next().unwrap_or(title);
+                let stem = stem.strip_suffix(".md").unwrap_or(stem);
+                self.by_stem.get(&stem.to_lowercase()).cloned()
+            }
+            LinkTarget::Path(dest) => {
+                let base = Path::new(from).parent().unwrap_or(Path::new(""));
+                self.by_path.get(&normalize_path(&base.join(dest))).cloned()
+            }
+        }
+    }
+}
+
+/// Notes that `path` links to, resolved against the vault, in link order.
+pub fn outbound_notes(path: &str, paths: &[String]) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let resolver = LinkResolver::new(paths);
+    let mut notes: Vec<String> = Vec::new();
+    for target in outgoing_links(&content) {
+        if let Some(note) = resolver.resolve(&target, path) {
+            if note != path && !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+    }
+    notes
+}
+
+/// Notes that link to `path`, sorted by path.
+pub fn inbound_notes(path: &str, paths: &[String]) -> Vec<String> {
+    let resolver = LinkResolver::new(paths);
+    let mut notes: Vec<String> = paths
+        .iter()
+        .filter(|from| from.as_str() != path)
+        .filter(|from| {
+            std::fs::read_to_string(from).is_ok_and(|content| {
+                outgoing_links(&content)
+                    .iter()
+                    .any(|link| resolver.resolve(link, from).as_deref() == Some(path))
+            })
+        })
+        .cloned()
+        .collect();
+    notes.sort();
+    notes
+}
+
+/// Counts incoming links per note by reading every note in the vault.
+/// Self-links are ignored, and a note linking another twice counts once.
+pub fn incoming_link_counts(paths: &[String]) -> HashMap<String, usize> {
+    let resolver = LinkResolver::new(paths);
+    let mut counts: HashMap<String, usize> = paths.iter().map(|p| (p.clone(), 0)).collect();
+    for path in paths {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let mut targets: Vec<String> = outgoing_links(&content)
+            .iter()
+            .filter_map(|link| resolver.resolve(link, path))
+            .filter(|target| target != path)
+            .collect();
+        targets.sort();
+        targets.dedup();
+        for target in targets {
+            *counts.entry(target).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Appends a `- [[title]]` list entry to the end of `target`, starting a new line
+/// first when the note doesn't end with one.
+pub fn append_wikilink(target: &str, title: &str) -> Result<(), String> {
+    let needs_newline =
+        std::fs::read(target).is_ok_and(|bytes| !bytes.is_empty() && !bytes.ends_with(b"\n"));
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(target)
+        .map_err(|e| format!("Could not open {}: {}", target, e))?;
+    let mut entry = String::new();
+    if needs_newline {
+        entry.push('\n');
+    }
+    entry.push_str(&format!("- [[{}]]\n", title));
+    file.write_all(entry.as_bytes())
+        .map_err(|e| format!("Could not write {}: {}", target, e))
+}
@@ -1,4 +1,13 @@
+pub mod clipboard;
 pub mod command_palette;
 pub mod core;
+pub mod debug_log;
+pub mod diff;
+pub mod export;
+pub mod frontmatter;
 pub mod highlight;
+pub mod journal;
+pub mod links;
+pub mod settings;
+pub mod tree;
 pub mod ui;
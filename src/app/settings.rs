@@ -0,0 +1,474 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Console-specific settings, stored next to the notemancy config as `console.yaml`.
+/// Every field has a default, so missing keys (or a missing file) are fine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Minimum time between two redraws, in milliseconds.
+    pub min_redraw_interval_ms: u64,
+    /// Time between two spinner frames, in milliseconds.
+    pub spinner_interval_ms: u64,
+    /// Longest wait for input before the event loop checks on background work,
+    /// in milliseconds.
+    pub poll_interval_ms: u64,
+    /// Show source line numbers in a gutter next to the preview.
+    pub preview_line_numbers: bool,
+    /// Return to the search view (query, results and selection intact) after
+    /// the editor, config editor or another external program exits.
+    pub keep_search_on_return: bool,
+    /// How many times to try an AI backend call (embedding server, vector
+    /// store) before reporting the error. Retries back off exponentially.
+    pub ai_max_attempts: u32,
+    /// What the preview does with raw HTML embedded in notes.
+    pub preview_html: HtmlMode,
+    /// Characters of context shown on each side of a match in result snippets.
+    pub snippet_context_chars: usize,
+    /// Maximum number of match fragments per snippet, joined with `…`.
+    pub snippet_max_fragments: usize,
+    /// External tools notes can be handed off to from the search view (`s`).
+    pub send_to: Vec<SendToCommand>,
+    /// Named queries listed under "Saved Searches" in the palette; `F` in the
+    /// search view adds the current one.
+    pub saved_searches: Vec<SavedSearch>,
+    /// Worker threads for the scan; unset means one per CPU. Read once at startup.
+    pub scan_threads: Option<usize>,
+    /// Where to land once the startup scan finishes.
+    pub startup_action: StartupAction,
+    /// Syntect theme for code blocks and the config preview. Unset uses the
+    /// notemancy config's `theme`, then base16-ocean.dark.
+    pub code_theme: Option<String>,
+    /// Number the first nine results `1.`–`9.`; the digit keys select them either way.
+    pub result_numbers: bool,
+    /// Look up related files for the top result as soon as results arrive, so
+    /// the related view opens instantly. Costs one AI call per search.
+    pub prewarm_related: bool,
+    /// Keep notes inside hidden (dot-prefixed) folders or files below the vault
+    /// root. Turning this off filters them out after the scan.
+    pub include_hidden: bool,
+    /// Where "Append to Daily Note" writes, relative to the vault root unless
+    /// absolute. `{YYYY-MM-DD}`, `{YYYY}`, `{MM}` and `{DD}` are filled in.
+    pub daily_note_path: String,
+    /// Blank columns on each side of the preview text.
+    pub preview_padding_x: u16,
+    /// Blank rows above and below the preview text.
+    pub preview_padding_y: u16,
+    /// Widest the preview text column may get; wider panes center it.
+    pub preview_max_width: Option<u16>,
+    /// What of a note is embedded to find its related files.
+    pub related_source: RelatedSource,
+    /// Characters embedded when `related_source` is `prefix`.
+    pub related_prefix_chars: usize,
+    /// What the AI backend's match scores mean, for turning them into similarity.
+    pub embedding_score: EmbeddingScore,
+    /// Frontmatter fields read as a note's search priority; the first one present wins.
+    pub priority_fields: Vec<String>,
+    /// How strongly priority boosts results: a note's score is multiplied by
+    /// `1 + priority_weight * priority`. Notes without a priority are unchanged.
+    pub priority_weight: f32,
+    /// What Enter does while typing a query.
+    pub editing_enter: EditingEnter,
+    /// Results fetched per search; `+` fetches this many more.
+    pub search_limit: usize,
+    /// Join the lines of a paragraph in the preview, as CommonMark renders single
+    /// newlines. Off keeps every source line on its own row.
+    pub join_soft_breaks: bool,
+    /// Show path, size, modification time and tags under the preview title (`i`).
+    pub preview_metadata: bool,
+    /// Two Esc presses within this many milliseconds return to the Preview screen
+    /// from anywhere. 0 turns the shortcut off.
+    pub double_esc_ms: u64,
+    /// Mark each result with a color picked from its top-level folder.
+    pub colorize_by_folder: bool,
+    /// What the preview does with code lines wider than the pane.
+    pub code_overflow: CodeOverflow,
+    /// Remember which notes have been previewed and mark the others with a dot.
+    pub track_seen: bool,
+    /// What to bring up to date after a note was changed in the editor.
+    pub reindex_after_edit: ReindexAfterEdit,
+    /// Show each result's frontmatter tags after its title.
+    pub result_tags: bool,
+    /// How rebuilds, re-embeds and prunes are confirmed.
+    pub confirm_style: ConfirmStyle,
+    /// Show how the query is sent to the search backend, under the search box.
+    pub show_query_interpretation: bool,
+    /// Refuse actions that write to the vault, and open notes read-only in vi-like editors.
+    pub read_only: bool,
+    /// Accent for the search input, selected rows and status messages: `#rrggbb`,
+    /// or `vault` for a color picked from the vault folder's name. Unset keeps the
+    /// default blue.
+    pub accent_color: Option<String>,
+    /// File that errors, status messages and scan, index and search events are
+    /// appended to with timestamps, e.g. `~/.cache/notemancy/console.log`.
+    /// Unset (the default) keeps logging off.
+    pub log_file: Option<String>,
+    /// Give the preview, search, palette and vector indexing screens each a faint
+    /// background tint, so the current mode shows at a glance.
+    pub state_tints: bool,
+    /// Short names typed into the palette filter to jump to a command, e.g.
+    /// `{ rs: "Rebuild Search Index" }`. These add to or replace the built-in ones.
+    pub command_aliases: HashMap<String, String>,
+    /// What opening a note that was deleted or moved since the scan does.
+    pub missing_note: MissingNote,
+}
+
+/// A user-defined "send to" action, e.g.
+/// `{ name: "Open in Obsidian", cmd: "obsidian {path}" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendToCommand {
+    pub name: String,
+    /// Shell command line; `{path}` is replaced with the selected note's path,
+    /// quoted for the shell.
+    pub cmd: String,
+}
+
+impl SendToCommand {
+    /// The command line with the placeholder filled in. A placeholder the user
+    /// already put in quotes is replaced quotes and all.
+    pub fn command_for(&self, path: &str) -> String {
+        self.cmd
+            .replace("\"{path}\"", "{path}")
+            .replace("'{path}'", "{path}")
+            .replace("{path}", &shell_quote(path))
+    }
+}
+
+/// Quotes `text` as a single `sh` word: wrapped in single quotes, with each
+/// `'` written as `'\''`.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// A named query, e.g. `{ name: "Untagged notes", query: "tags:none" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Handling of raw HTML in the markdown preview.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HtmlMode {
+    /// Drop HTML entirely.
+    #[default]
+    Strip,
+    /// Show the HTML source, dimmed.
+    Raw,
+    /// Render common tags (`<b>`, `<i>`, `<u>`, `<br>`) and hide the rest.
+    Render,
+}
+
+/// The screen shown after the startup scan.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupAction {
+    /// The welcome screen.
+    #[default]
+    Preview,
+    /// Index and go straight into search.
+    Search,
+    /// Open the command palette.
+    Palette,
+    /// Index and re-run the query that was on screen when the app last quit.
+    LastSession,
+}
+
+/// The text embedded for a related-files lookup. notemancy_core doesn't hand out
+/// the stored embedding of a note, so every option embeds text afresh.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelatedSource {
+    /// The whole note. Best matches, but slow for long notes and may overflow
+    /// the embedding model's context.
+    #[default]
+    Full,
+    /// The first `related_prefix_chars` characters.
+    Prefix,
+    /// The title and headings only; fast, but only as good as the outline.
+    Outline,
+}
+
+impl RelatedSource {
+    /// The part of a note to embed, given its title and content.
+    pub fn text_for(self, title: &str, content: &str, prefix_chars: usize) -> String {
+        match self {
+            RelatedSource::Full => content.to_string(),
+            RelatedSource::Prefix => content.chars().take(prefix_chars).collect(),
+            RelatedSource::Outline => {
+                let mut outline = vec![title.to_string()];
+                let mut in_fence = false;
+                for line in content.lines() {
+                    let trimmed = line.trim_start();
+                    if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                        in_fence = !in_fence;
+                    } else if !in_fence && trimmed.starts_with('#') {
+                        outline.push(trimmed.trim_start_matches('#').trim().to_string());
+                    }
+                }
+                outline.join("\n")
+            }
+        }
+    }
+}
+
+/// Confirmation asked before a destructive or expensive action runs.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmStyle {
+    /// Press `y` to go ahead, anything else cancels.
+    #[default]
+    YesNo,
+    /// Type the word "yes" and press Enter.
+    TypeYes,
+    /// Run straight away.
+    None,
+}
+
+/// Indexes refreshed once the editor exits with a note changed.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReindexAfterEdit {
+    /// Nothing; run the indexing commands by hand.
+    #[default]
+    Off,
+    /// Rescan and rebuild the full-text index.
+    Search,
+    /// Also re-embed for related files. Costs AI calls for the whole vault.
+    All,
+}
+
+/// Handling of a result whose file is gone when it is opened. It is dropped
+/// from the results either way, instead of the editor creating it anew.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingNote {
+    /// Ask whether to rescan the vault.
+    #[default]
+    Ask,
+    /// Only drop it from the results.
+    Remove,
+    /// Rescan the vault right away.
+    Rescan,
+}
+
+/// Handling of code lines wider than the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeOverflow {
+    /// Wrap them like prose.
+    #[default]
+    Wrap,
+    /// Cut them at the pane's edge, ending in `→`.
+    Truncate,
+}
+
+/// The kind of score the embedding backend returns with each match.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingScore {
+    /// A distance where 0 is identical and 1 is unrelated (`1 - cosine`).
+    #[default]
+    CosineDistance,
+    /// A similarity where higher is better, e.g. cosine similarity.
+    Similarity,
+    /// An unbounded distance such as euclidean; 0 is identical.
+    Distance,
+}
+
+impl EmbeddingScore {
+    /// Similarity in 0..=1, higher is better.
+    pub fn similarity(self, score: f32) -> f32 {
+        let similarity = match self {
+            EmbeddingScore::CosineDistance => 1.0 - score,
+            EmbeddingScore::Similarity => score,
+            EmbeddingScore::Distance => 1.0 / (1.0 + score.max(0.0)),
+        };
+        similarity.clamp(0.0, 1.0)
+    }
+}
+
+/// Behavior of Enter in the search box. Every option runs the search first.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditingEnter {
+    /// Keep editing the query.
+    Stay,
+    /// Switch to normal mode to browse the results.
+    #[default]
+    Normal,
+    /// Open the top result in the editor. Semantic results arrive later, so
+    /// semantic searches switch to normal mode instead.
+    Open,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            min_redraw_interval_ms: 16,
+            spinner_interval_ms: 100,
+            poll_interval_ms: 100,
+            preview_line_numbers: false,
+            keep_search_on_return: false,
+            ai_max_attempts: 3,
+            preview_html: HtmlMode::Strip,
+            snippet_context_chars: 40,
+            snippet_max_fragments: 1,
+            send_to: Vec::new(),
+            saved_searches: Vec::new(),
+            scan_threads: None,
+            startup_action: StartupAction::Preview,
+            code_theme: None,
+            result_numbers: true,
+            prewarm_related: true,
+            include_hidden: true,
+            daily_note_path: "journal/{YYYY-MM-DD}.md".to_string(),
+            preview_padding_x: 2,
+            preview_padding_y: 1,
+            preview_max_width: None,
+            related_source: RelatedSource::Full,
+            related_prefix_chars: 2000,
+            embedding_score: EmbeddingScore::CosineDistance,
+            priority_fields: vec!["priority".to_string(), "weight".to_string()],
+            priority_weight: 0.2,
+            editing_enter: EditingEnter::Normal,
+            search_limit: 20,
+            join_soft_breaks: true,
+            preview_metadata: false,
+            double_esc_ms: 400,
+            colorize_by_folder: false,
+            code_overflow: CodeOverflow::Wrap,
+            track_seen: true,
+            reindex_after_edit: ReindexAfterEdit::Off,
+            result_tags: false,
+            confirm_style: ConfirmStyle::YesNo,
+            show_query_interpretation: false,
+            read_only: false,
+            accent_color: None,
+            log_file: None,
+            state_tints: false,
+            command_aliases: HashMap::new(),
+            missing_note: MissingNote::Ask,
+        }
+    }
+}
+
+impl Settings {
+    /// Number of scan worker threads to use.
+    pub fn scan_thread_count(&self) -> usize {
+        self.scan_threads
+            .filter(|&n| n > 0)
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// Location of the settings file, e.g. `~/.config/notemancy/console.yaml`.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("notemancy").join("console.yaml"))
+    }
+
+    /// Loads the settings file, falling back to defaults when it doesn't exist.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        serde_yaml::from_str(&contents)
+            .map_err(|e| format!("Invalid settings in {}: {}", path.display(), e))
+    }
+
+    /// Writes the settings back to disk, creating the config directory if needed.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No config directory available")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = serde_yaml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(&path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+}
+
+/// Where the last search query is remembered between runs, e.g.
+/// `~/.cache/notemancy/last_query`.
+fn last_query_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("notemancy").join("last_query"))
+}
+
+/// The search query on screen when the app last quit, if any.
+pub fn load_last_query() -> Option<String> {
+    let query = fs::read_to_string(last_query_path()?).ok()?;
+    let query = query.trim();
+    (!query.is_empty()).then(|| query.to_string())
+}
+
+/// Remembers `query` for the `last_session` startup action.
+pub fn save_last_query(query: &str) -> Result<(), String> {
+    let path = last_query_path().ok_or("No cache directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, query).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Where previewed notes are remembered, one path per line, e.g.
+/// `~/.cache/notemancy/seen`.
+fn seen_notes_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("notemancy").join("seen"))
+}
+
+/// Every note previewed in an earlier session.
+pub fn load_seen_notes() -> HashSet<String> {
+    seen_notes_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|seen| seen.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Adds `note` to the seen list.
+pub fn save_seen_note(note: &str) -> Result<(), String> {
+    let path = seen_notes_path().ok_or("No cache directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", note).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Most queries kept in the search history.
+pub const SEARCH_HISTORY_LIMIT: usize = 100;
+
+/// Where queries run with Enter are remembered, oldest first, next to the
+/// config, e.g. `~/.config/notemancy/search_history`.
+fn search_history_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("notemancy").join("search_history"))
+}
+
+/// Queries from earlier sessions, oldest first.
+pub fn load_search_history() -> Vec<String> {
+    search_history_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|history| history.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Replaces the stored history with `history`.
+pub fn save_search_history(history: &[String]) -> Result<(), String> {
+    let path = search_history_path().ok_or("No config directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut contents = history.join("\n");
+    contents.push('\n');
+    fs::write(&path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
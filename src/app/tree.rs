@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A folder or note in the sidebar tree.
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    // Folders first, then notes, each sorted by name
+    pub children: Vec<TreeNode>,
+}
+
+/// One visible line of the tree, after collapsed folders are skipped.
+#[derive(Debug, Clone)]
+pub struct TreeRow {
+    pub depth: usize,
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub expanded: bool,
+}
+
+/// Folder tree of the vault, built from the scanned note paths.
+#[derive(Debug, Clone)]
+pub struct FolderTree {
+    pub root: TreeNode,
+}
+
+impl FolderTree {
+    /// Builds the tree rooted at the deepest folder containing every note.
+    pub fn build(paths: &[String]) -> Self {
+        let root_path = common_ancestor(paths);
+        let mut root = TreeNode {
+            name: root_path.file_name().map_or_else(
+                || root_path.display().to_string(),
+                |n| n.to_string_lossy().to_string(),
+            ),
+            path: root_path.clone(),
+            is_dir: true,
+            children: Vec::new(),
+        };
+        for path in paths {
+            let path = Path::new(path);
+            let Ok(relative) = path.strip_prefix(&root_path) else {
+                continue;
+            };
+            insert(&mut root, relative, path);
+        }
+        sort(&mut root);
+        Self { root }
+    }
+
+    /// Flattens the tree into the rows currently visible. The root itself is not shown.
+    pub fn visible_rows(&self, expanded: &HashSet<PathBuf>) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        for child in &self.root.children {
+            push_rows(child, 0, expanded, &mut rows);
+        }
+        rows
+    }
+}
+
+/// The deepest folder containing every path.
+pub fn common_ancestor(paths: &[String]) -> PathBuf {
+    let mut ancestor: Option<PathBuf> = None;
+    for path in paths {
+        let parent = Path::new(path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        ancestor = Some(match ancestor {
+            None => parent,
+            Some(current) => current
+                .components()
+                .zip(parent.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    ancestor.unwrap_or_default()
+}
+
+fn insert(node: &mut TreeNode, relative: &Path, full: &Path) {
+    let mut components = relative.components();
+    let Some(first) = components.next() else {
+        return;
+    };
+    let name = first.as_os_str().to_string_lossy().to_string();
+    let rest = components.as_path();
+    if rest.as_os_str().is_empty() {
+        node.children.push(TreeNode {
+            name,
+            path: full.to_path_buf(),
+            is_dir: false,
+            children: Vec::new(),
+        });
+        return;
+    }
+
+    let index = match node
+        .children
+        .iter()
+        .position(|child| child.is_dir && child.name == name)
+    {
+        Some(index) => index,
+        None => {
+            node.children.push(TreeNode {
+                path: node.path.join(&name),
+                name,
+                is_dir: true,
+                children: Vec::new(),
+            });
+            node.children.len() - 1
+        }
+    };
+    insert(&mut node.children[index], rest, full);
+}
+
+fn sort(node: &mut TreeNode) {
+    node.children.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    for child in &mut node.children {
+        sort(child);
+    }
+}
+
+fn push_rows(node: &TreeNode, depth: usize, expanded: &HashSet<PathBuf>, rows: &mut Vec<TreeRow>) {
+    let is_expanded = node.is_dir && expanded.contains(&node.path);
+    rows.push(TreeRow {
+        depth,
+        name: node.name.clone(),
+        path: node.path.clone(),
+        is_dir: node.is_dir,
+        expanded: is_expanded,
+    });
+    if is_expanded {
+        for child in &node.children {
+            push_rows(child, depth + 1, expanded, rows);
+        }
+    }
+}
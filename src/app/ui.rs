@@ -3,50 +3,125 @@ use ratatui::{
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
-    widgets::{List, ListItem, Padding},
+    widgets::{List, ListItem, Padding, Wrap},
     Frame,
 };
 
-use crate::app::core::App;
-use crate::app::core::{DetailViewMode, InputMode};
-use crate::app::highlight::{highlight_full_markdown, highlight_matches};
+use crate::app::core::{App, AppState, CachedRender};
+use crate::app::core::{DetailViewMode, InputMode, PreviewLayout, SearchMode};
+use crate::app::diff::{DiffLine, IndexStatus};
+use crate::app::highlight::{highlight_matches, render_markdown, RenderOptions, RenderedMarkdown};
+use crate::app::settings::{CodeOverflow, ConfirmStyle};
 use std::fs;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
     let area = frame.area();
+    let interpretation = app
+        .settings
+        .show_query_interpretation
+        .then(|| app.query_interpretation())
+        .flatten();
+    let interpretation_height = if interpretation.is_some() { 1 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(interpretation_height),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
         .split(area);
 
     // Search input at the top (same as before)
     // Search input at top with visual indicator of input mode
-    let mut padded_input = format!(" {} ", app.search_query);
+    let mut padded_input = match app.result_list_title {
+        // Command-produced lists show their label until a query is typed
+        Some(ref title) if app.search_query.is_empty() => format!(" [{}] ", title),
+        _ => format!(" {} ", app.search_query),
+    };
 
     // Add cursor indicator if in editing mode
     if app.input_mode == InputMode::Editing {
         padded_input.push('|'); // Simple cursor
     }
 
+    let input_fg = app.accent.unwrap_or(Color::Rgb(69, 137, 255));
     let input_style = match app.input_mode {
-        InputMode::Editing => Style::default()
-            .fg(Color::Rgb(69, 137, 255))
-            .bg(Color::Rgb(40, 40, 40)), // Slightly brighter background when editing
-        InputMode::Normal => Style::default()
-            .fg(Color::Rgb(69, 137, 255))
-            .bg(Color::Rgb(30, 30, 30)),
+        InputMode::Editing => Style::default().fg(input_fg).bg(Color::Rgb(40, 40, 40)), // Slightly brighter background when editing
+        InputMode::Normal => Style::default().fg(input_fg).bg(Color::Rgb(30, 30, 30)),
     };
 
-    let input = Line::from(padded_input).style(input_style);
+    // Badge showing which search backend the query goes to
+    let mode_badge = match app.search_mode {
+        SearchMode::Lexical => Span::styled(
+            " LEXICAL ",
+            Style::default().bg(Color::Rgb(60, 60, 60)).fg(Color::White),
+        ),
+        SearchMode::Semantic => Span::styled(
+            " SEMANTIC ",
+            Style::default()
+                .bg(Color::Rgb(138, 63, 252))
+                .fg(Color::White),
+        ),
+    };
+    let mut input_spans = vec![mode_badge, Span::raw(padded_input)];
+    if !app.search_query.is_empty() && !app.is_semantic_searching {
+        let count = if app.more_results_available() {
+            format!(
+                " {} results (more available, + to load) ",
+                app.search_results.len()
+            )
+        } else {
+            format!(" {} results ", app.search_results.len())
+        };
+        input_spans.push(Span::styled(
+            count,
+            Style::default().fg(Color::Rgb(120, 120, 120)),
+        ));
+    }
+    if app.is_semantic_searching {
+        let spinner = app.spinner_chars[app.spinner_idx];
+        input_spans.push(Span::styled(
+            format!(" {} ", spinner),
+            Style::default().fg(Color::Rgb(255, 204, 0)),
+        ));
+    }
+
+    let input = Line::from(input_spans).style(input_style);
     frame.render_widget(input, chunks[0]);
+    if let Some(interpretation) = interpretation {
+        let line = Line::from(Span::styled(
+            format!(" interpreted as: {}", interpretation),
+            Style::default().fg(Color::Rgb(120, 120, 120)),
+        ))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+        frame.render_widget(line, chunks[1]);
+    }
+
+    // The folder tree sidebar, when shown, takes a column on the left
+    let main_area = if app.sidebar_visible {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+            .split(chunks[2]);
+        draw_sidebar(app, frame, columns[0]);
+        columns[1]
+    } else {
+        chunks[2]
+    };
 
     // Split bottom area for results list and detail panel
     let bottom_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-        .split(chunks[1]);
+        .split(main_area);
 
     // Render search results list (same as before)
+    let list_width = bottom_chunks[0].width as usize;
+    let vault_root = app.settings.colorize_by_folder.then(|| app.vault_root());
     let items: Vec<ListItem> = app
         .search_results
         .iter()
@@ -55,7 +130,7 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
             let style = if i == app.selected_search_index {
                 Style::default()
                     .fg(Color::Rgb(224, 224, 224))
-                    .bg(Color::Rgb(70, 130, 180))
+                    .bg(selection_bg(app))
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
@@ -73,51 +148,245 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
                 result.title.clone()
             };
 
-            ListItem::new(Span::styled(format!(" {} ", display_text), style))
+            // Quick-select numbers for the results reachable with the digit keys
+            let number = if app.settings.result_numbers && i < 9 {
+                format!("{}.", i + 1)
+            } else if app.settings.result_numbers {
+                "  ".to_string()
+            } else {
+                String::new()
+            };
+            let mut title_width = list_width.saturating_sub(3 + number.width());
+            let mut title_spans = vec![Span::styled(
+                format!(" {}", number),
+                style.fg(Color::Rgb(120, 120, 120)),
+            )];
+            if app.settings.track_seen {
+                let marker = if app.seen_notes.contains(&result.path) {
+                    Span::styled(" ", style)
+                } else {
+                    Span::styled("•", style.fg(Color::Rgb(69, 137, 255)))
+                };
+                title_spans.push(marker);
+                title_width = title_width.saturating_sub(1);
+            }
+            if let Some(ref root) = vault_root {
+                // Notes at the top of the vault get a blank so titles stay aligned
+                let marker = match top_level_folder(root, &result.path) {
+                    Some(folder) => Span::styled(" ▌", style.fg(folder_color(&folder))),
+                    None => Span::styled("  ", style),
+                };
+                title_spans.push(marker);
+                title_width = title_width.saturating_sub(2);
+            }
+            // Tags get at most half the row; the title keeps the rest
+            let tags = match app.tag_cache.get(&result.path) {
+                Some((_, tags)) if app.settings.result_tags => tags.as_slice(),
+                _ => &[],
+            };
+            let chips = tag_chips(tags, title_width / 2, style);
+            let chips_width: usize = chips.iter().map(|chip| chip.width()).sum();
+            title_spans.push(Span::styled(
+                format!(
+                    " {} ",
+                    truncate_to_width(&display_text, title_width.saturating_sub(chips_width))
+                ),
+                style,
+            ));
+            title_spans.extend(chips);
+            let title_line = Line::from(title_spans);
+            match app.result_snippets.get(&result.path) {
+                Some(snippet) => {
+                    let snippet_style = if i == app.selected_search_index {
+                        style.remove_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                            .fg(Color::Rgb(120, 120, 120))
+                            .bg(Color::Rgb(22, 22, 22))
+                    };
+                    let snippet_line = Line::from(Span::styled(
+                        format!(
+                            "   {} ",
+                            truncate_to_width(snippet, list_width.saturating_sub(4))
+                        ),
+                        snippet_style,
+                    ));
+                    ListItem::new(vec![title_line, snippet_line])
+                }
+                None => ListItem::new(title_line),
+            }
         })
         .collect();
 
-    let results_list = List::new(items).style(Style::default().bg(Color::Rgb(22, 22, 22)));
-    frame.render_widget(results_list, bottom_chunks[0]);
+    if items.is_empty() && app.semantic_search_error.is_some() {
+        let error = app.semantic_search_error.as_deref().unwrap_or_default();
+        let error_paragraph = Paragraph::new(format!(" Error: {}", error)).style(
+            Style::default()
+                .fg(Color::Rgb(255, 0, 0))
+                .bg(Color::Rgb(22, 22, 22)),
+        );
+        frame.render_widget(error_paragraph, bottom_chunks[0]);
+    } else {
+        let results_list = List::new(items).style(Style::default().bg(Color::Rgb(22, 22, 22)));
+        // The state keeps its offset between frames, so the list only scrolls
+        // once the selection reaches the edge of the pane
+        app.results_list_state.select(Some(app.selected_search_index));
+        frame.render_stateful_widget(
+            results_list,
+            bottom_chunks[0],
+            &mut app.results_list_state,
+        );
+        // Lexical search finishes before the next frame; a semantic one leaves
+        // the previous query's results on screen until its own arrive
+        if app.is_semantic_searching {
+            frame.buffer_mut().set_style(
+                bottom_chunks[0],
+                Style::default().fg(Color::Rgb(110, 110, 110)),
+            );
+        }
+    }
+
+    // Focus mode keeps the other panes visible but pushes them into the background
+    if app.focus_mode {
+        let dimmed = Style::default()
+            .fg(Color::Rgb(90, 90, 90))
+            .add_modifier(Modifier::DIM);
+        frame.buffer_mut().set_style(chunks[0], dimmed);
+        frame.buffer_mut().set_style(bottom_chunks[0], dimmed);
+    }
+    let preview_fg = if app.focus_mode {
+        Color::Rgb(245, 245, 245)
+    } else {
+        Color::Rgb(224, 224, 224)
+    };
 
     // Render the detail panel based on the current mode
     match app.detail_view_mode {
         DetailViewMode::Preview => {
-            // Existing preview logic
-            if let Some(result) = app.search_results.get(app.selected_search_index) {
-                let content = match fs::read_to_string(&result.path) {
-                    Ok(content) => content,
-                    Err(e) => format!("Error reading file: {}", e),
+            let selected = app.search_results.get(app.selected_search_index);
+            let diff = match (&app.preview_diff, selected) {
+                (Some((path, diff)), Some(result)) if *path == result.path => Some(diff),
+                _ => None,
+            };
+            if let (Some(diff), Some(result)) = (diff, selected) {
+                let lines: Vec<Line> = diff.iter().map(diff_line).collect();
+                let diff_block = Block::default()
+                    .title(format!(
+                        "Changes since last index: {} (Esc to close)",
+                        result.title
+                    ))
+                    .padding(Padding {
+                        left: 2,
+                        right: 2,
+                        top: 1,
+                        bottom: 1,
+                    })
+                    .borders(Borders::NONE);
+                // The diff isn't wrapped, so each line is one row
+                app.preview_layout = PreviewLayout {
+                    heading_rows: Vec::new(),
+                    total_rows: lines.len(),
+                    page_height: diff_block.inner(bottom_chunks[1]).height as usize,
+                };
+                let diff_view = Paragraph::new(lines)
+                    .style(Style::default().fg(preview_fg).bg(Color::Rgb(38, 38, 38)))
+                    .block(diff_block)
+                    .scroll((app.preview_scroll, 0));
+                frame.render_widget(diff_view, bottom_chunks[1]);
+            } else if let Some(result) = app.preview_result() {
+                // Cached text of another note is never shown, even for a frame
+                let content = match app.preview_content {
+                    Some(ref cached) if cached.path == result.path => cached.content.clone(),
+                    _ => "Loading...".to_string(),
                 };
 
-                let mut highlighted = highlight_full_markdown(&content);
+                // The metadata strip takes the row under the title
+                let mut padding = preview_padding(app, bottom_chunks[1].width);
+                if app.settings.preview_metadata {
+                    padding.top += 1;
+                }
+
+                let mut code_width = None;
+                if app.settings.code_overflow == CodeOverflow::Truncate {
+                    let mut width = bottom_chunks[1]
+                        .width
+                        .saturating_sub(padding.left + padding.right)
+                        as usize;
+                    if app.settings.preview_line_numbers {
+                        // Gutter is the widest line number plus " │ "
+                        width = width.saturating_sub(content.lines().count().to_string().len() + 3);
+                    }
+                    code_width = Some(width);
+                }
+                let rendered = cached_render(app, &result.path, &content, code_width);
+                let mut highlighted = rendered.lines;
                 if !app.search_query.is_empty() {
                     highlighted = highlighted
                         .into_iter()
                         .map(|line| highlight_matches(&line, &app.search_query))
                         .collect();
                 }
-
+                if app.settings.preview_line_numbers {
+                    highlighted = with_line_numbers(highlighted, &rendered.source_lines);
+                }
                 let preview_block = Block::default()
-                    .title(format!("Preview: {}", result.title))
-                    .padding(Padding {
-                        left: 2,
-                        right: 2,
-                        top: 1,
-                        bottom: 1,
-                    })
+                    .title(Line::from(vec![
+                        Span::raw(if app.preview_locked_path.is_some() {
+                            format!("Preview (pinned): {} ", result.title)
+                        } else {
+                            format!("Preview: {} ", result.title)
+                        }),
+                        index_status_span(crate::app::diff::index_status(
+                            app.indexed_times.get(&result.path).copied(),
+                            // The note's modification time as of its last read
+                            app.preview_content
+                                .as_ref()
+                                .filter(|cached| cached.path == result.path)
+                                .and_then(|cached| cached.modified),
+                        )),
+                    ]))
+                    .padding(padding)
                     .borders(Borders::NONE);
 
+                // Record where each heading lands once lines wrap, for section paging.
+                // Rows are estimated from display width; word wrapping can add a few.
+                let inner = preview_block.inner(bottom_chunks[1]);
+                let width = (inner.width as usize).max(1);
+                let mut line_rows = Vec::with_capacity(highlighted.len());
+                let mut total_rows = 0;
+                for line in &highlighted {
+                    line_rows.push(total_rows);
+                    total_rows += line.width().div_ceil(width).max(1);
+                }
+                app.preview_layout = PreviewLayout {
+                    heading_rows: rendered
+                        .headings
+                        .iter()
+                        .filter_map(|heading| line_rows.get(heading.line).copied())
+                        .collect(),
+                    total_rows,
+                    page_height: inner.height as usize,
+                };
+
                 let preview = Paragraph::new(highlighted)
-                    .style(
-                        Style::default()
-                            .fg(Color::Rgb(224, 224, 224))
-                            .bg(Color::Rgb(38, 38, 38)),
-                    )
+                    .style(Style::default().fg(preview_fg).bg(Color::Rgb(38, 38, 38)))
                     .alignment(ratatui::layout::Alignment::Left)
-                    .block(preview_block);
+                    .block(preview_block)
+                    .wrap(Wrap { trim: false })
+                    .scroll((app.preview_scroll, 0));
 
                 frame.render_widget(preview, bottom_chunks[1]);
+
+                if app.settings.preview_metadata && bottom_chunks[1].height > 1 {
+                    let strip = Rect {
+                        x: inner.x,
+                        y: bottom_chunks[1].y + 1,
+                        width: inner.width,
+                        height: 1,
+                    };
+                    let metadata = metadata_line(&result.path, &content);
+                    frame.render_widget(Paragraph::new(metadata), strip);
+                }
             } else {
                 let preview = Paragraph::new("No file selected.")
                     .style(
@@ -131,10 +400,15 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
         }
         DetailViewMode::RelatedFiles => {
             // New logic for displaying related files
+            let metric = format!(
+                "{}, {}",
+                app.similarity_metric.label(),
+                app.related_ranking.label()
+            );
             let title = if let Some(result) = app.search_results.get(app.selected_search_index) {
-                format!("Related Files: {}", result.title)
+                format!("Related Files [{}]: {}", metric, result.title)
             } else {
-                "Related Files".to_string()
+                format!("Related Files [{}]", metric)
             };
 
             let related_block = Block::default()
@@ -175,11 +449,18 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
 
                 frame.render_widget(error_paragraph, bottom_chunks[1]);
             } else if !app.related_files.is_empty() {
-                // Display the list of related files
+                // Display the list of related files, fitting each entry inside the block padding
+                // List on the left, the highlighted related note on the right
+                let related_columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
+                    .split(bottom_chunks[1]);
+                let related_width = related_columns[0].width.saturating_sub(4) as usize;
                 let related_items: Vec<ListItem> = app
                     .related_files
                     .iter()
-                    .map(|result| {
+                    .enumerate()
+                    .map(|(i, result)| {
                         let display_text = if result.title.is_empty() {
                             let path = std::path::Path::new(&result.path);
                             path.file_name()
@@ -190,16 +471,31 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
                             result.title.clone()
                         };
 
+                        let title_style = if i == app.related_selected {
+                            Style::default()
+                                .fg(Color::Rgb(224, 224, 224))
+                                .bg(selection_bg(app))
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default().fg(Color::Rgb(224, 224, 224))
+                        };
+
+                        // The title gets the room it needs first; the path fills whatever is left
+                        let title_text = format!(
+                            "• {} ",
+                            truncate_to_width(&display_text, related_width.saturating_sub(3))
+                        );
+                        let path_width = related_width.saturating_sub(title_text.width());
+                        let path_text = if path_width > 2 {
+                            format!("({})", truncate_to_width(&result.path, path_width - 2))
+                        } else {
+                            String::new()
+                        };
+
                         // Create a Line from multiple spans
                         ListItem::new(Line::from(vec![
-                            Span::styled(
-                                format!("• {} ", display_text),
-                                Style::default().fg(Color::Rgb(224, 224, 224)),
-                            ),
-                            Span::styled(
-                                format!("({})", result.path),
-                                Style::default().fg(Color::Rgb(150, 150, 150)),
-                            ),
+                            Span::styled(title_text, title_style),
+                            Span::styled(path_text, Style::default().fg(Color::Rgb(150, 150, 150))),
                         ]))
                     })
                     .collect();
@@ -212,7 +508,8 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
                     )
                     .block(related_block);
 
-                frame.render_widget(related_list, bottom_chunks[1]);
+                frame.render_widget(related_list, related_columns[0]);
+                draw_related_preview(app, frame, related_columns[1]);
             } else {
                 // Display a message when there are no related files
                 let related_msg = Paragraph::new("No related files found.")
@@ -229,6 +526,22 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
         }
     }
 
+    if app.link_picker.is_some() {
+        draw_link_picker(app, frame, area);
+    }
+    if app.link_graph.is_some() {
+        draw_link_graph(app, frame, area);
+    }
+    if app.quick_look.is_some() {
+        draw_quick_look(app, frame, area);
+    }
+    if app.link_inserter.is_some() {
+        draw_link_inserter(app, frame, area);
+    }
+    if app.send_to_selected.is_some() {
+        draw_send_to_menu(app, frame, area);
+    }
+
     // Add a help text at the bottom of the screen
     let help_area = Rect {
         x: area.x,
@@ -248,6 +561,17 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
         "NORMAL"
     };
 
+    // A fresh status message takes over the help line until it expires
+    if let Some(status) = app.current_status() {
+        let status_paragraph = Paragraph::new(format!(" {}", status)).style(
+            Style::default()
+                .fg(app.accent.unwrap_or(Color::Rgb(224, 224, 224)))
+                .bg(Color::Rgb(22, 22, 22)),
+        );
+        frame.render_widget(status_paragraph, help_area);
+        return;
+    }
+
     let help_text = Line::from(vec![
         Span::styled(" ESC ", Style::default().bg(Color::Gray).fg(Color::Black)),
         Span::raw(if app.input_mode == InputMode::Editing {
@@ -259,6 +583,53 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
         Span::raw(" Enter Edit Mode | "),
         Span::styled(" Tab/r ", Style::default().bg(Color::Gray).fg(Color::Black)),
         Span::raw(format!(" Toggle View [{}] | ", mode_text)),
+        Span::styled(" ^T ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Lexical/Semantic | "),
+        Span::styled(" ^L ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Open Top Result | "),
+        Span::styled(" y ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Copy Link | "),
+        Span::styled(" Y ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Copy Related | "),
+        Span::styled(" o ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Open URL | "),
+        Span::styled(" w ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Wikilinks | "),
+        Span::styled(" g ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Link Graph | "),
+        Span::styled(" L ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Link Into | "),
+        Span::styled(" Space ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Quick Look | "),
+        Span::styled(" d ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Folder | "),
+        Span::styled(" s ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Send To | "),
+        Span::styled(" f ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Focus | "),
+        Span::styled(" b ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Folders | "),
+        Span::styled(" # ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Line Numbers | "),
+        Span::styled(" i ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" File Info | "),
+        Span::styled(" p ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Pin Preview | "),
+        Span::styled(" ^D/^U ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Scroll Preview | "),
+        Span::styled(
+            " S/Bksp ",
+            Style::default().bg(Color::Gray).fg(Color::Black),
+        ),
+        Span::raw(" Search Title/Back | "),
+        Span::styled(" U ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Reindex Note | "),
+        Span::styled(" F ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Save Search | "),
+        Span::styled(" t/T ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Theme/Save | "),
+        Span::styled(" m/h ", Style::default().bg(Color::Gray).fg(Color::Black)),
+        Span::raw(" Metric/Ranking | "),
         Span::styled(
             format!(" {} ", mode_indicator),
             Style::default().bg(Color::Blue).fg(Color::White),
@@ -273,32 +644,698 @@ pub fn draw_search_ui(app: &mut App, frame: &mut Frame) {
 
 pub fn draw_command_palette(app: &App, frame: &mut Frame, area: Rect) {
     use ratatui::widgets::{Block, Borders, List, ListItem};
+    let path = crate::app::command_palette::palette_path(app);
+    let mut title = if path.is_empty() {
+        "Command Palette".to_string()
+    } else {
+        format!("Command Palette › {}", path)
+    };
+    if !app.palette_filter.is_empty() {
+        title.push_str(&format!(" [{}]", app.palette_filter));
+    }
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Command Palette")
+        .title(title)
         .border_style(Style::default().fg(Color::Cyan));
 
     let inner_area = centered_rect(60, 30, area);
+    let mut items: Vec<ListItem> = Vec::new();
+    let visible = crate::app::command_palette::visible_commands(app);
+    for (i, cmd) in visible
+        .iter()
+        .map(|&index| &app.command_items[index])
+        .enumerate()
+    {
+        let style = if i == app.selected_command_index {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        // Categories are marked so it's clear Enter opens a sub-list
+        let marker = if cmd.children.is_empty() { "" } else { " ›" };
+        let content = format!("{}{} - {}", cmd.name, marker, cmd.description);
+        items.push(ListItem::new(Span::styled(content, style)));
+
+        // Show the inline search launcher beneath the highlighted "Search" command
+        if i == app.selected_command_index && cmd.name == "Search" {
+            items.extend(palette_search_items(app));
+        }
+    }
+
+    let list = List::new(items).block(block);
+    frame.render_widget(list, inner_area);
+}
+
+/// Draws the folder tree sidebar.
+fn draw_sidebar(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref tree) = app.folder_tree else {
+        return;
+    };
+    let border_color = if app.sidebar_focused {
+        Color::Cyan
+    } else {
+        Color::Rgb(60, 60, 60)
+    };
+    let block = Block::default()
+        .title(tree.root.name.clone())
+        .borders(Borders::RIGHT)
+        .border_style(Style::default().fg(border_color))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
+    let rows = tree.visible_rows(&app.tree_expanded);
+    let selected = app.tree_selected.min(rows.len().saturating_sub(1));
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let icon = match (row.is_dir, row.expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            };
+            let style = if i == selected && app.sidebar_focused {
+                Style::default()
+                    .fg(Color::Rgb(224, 224, 224))
+                    .bg(selection_bg(app))
+                    .add_modifier(Modifier::BOLD)
+            } else if row.is_dir {
+                Style::default().fg(Color::Rgb(69, 137, 255))
+            } else {
+                Style::default().fg(Color::Rgb(198, 198, 198))
+            };
+            let indent = "  ".repeat(row.depth);
+            ListItem::new(Span::styled(
+                format!("{}{}{}", indent, icon, row.name),
+                style,
+            ))
+        })
+        .collect();
+
+    // Keep the selection in view
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(List::new(items).block(block), area, &mut state);
+}
+
+/// Styles one diff line: additions green, removals red, unchanged lines dimmed.
+fn diff_line(line: &DiffLine) -> Line<'static> {
+    match line {
+        DiffLine::Added(text) => Line::from(Span::styled(
+            format!("+ {}", text),
+            Style::default().fg(Color::Rgb(66, 190, 101)),
+        )),
+        DiffLine::Removed(text) => Line::from(Span::styled(
+            format!("- {}", text),
+            Style::default().fg(Color::Rgb(250, 77, 86)),
+        )),
+        DiffLine::Same(text) => Line::from(Span::styled(
+            format!("  {}", text),
+            Style::default().fg(Color::Rgb(141, 141, 141)),
+        )),
+    }
+}
+
+/// Prefixes each rendered line with a dimmed gutter holding its source line number.
+fn with_line_numbers(
+    lines: Vec<Line<'static>>,
+    source_lines: &[Option<usize>],
+) -> Vec<Line<'static>> {
+    let width = source_lines
+        .iter()
+        .flatten()
+        .max()
+        .map_or(1, |n| n.to_string().len());
+    let gutter_style = Style::default().fg(Color::Rgb(110, 110, 110));
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let number = match source_lines.get(i).copied().flatten() {
+                Some(n) => format!("{:>width$} │ ", n, width = width),
+                None => format!("{:>width$} │ ", "", width = width),
+            };
+            let mut spans = vec![Span::styled(number, gutter_style)];
+            spans.extend(line.spans);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Builds the input line and top matches shown under the palette's "Search" command.
+fn palette_search_items(app: &App) -> Vec<ListItem<'static>> {
+    let dim = Style::default().fg(Color::Rgb(150, 150, 150));
+    if !app.palette_search_active {
+        return vec![ListItem::new(Span::styled("    Tab to search inline", dim))];
+    }
+
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::styled("    › ", Style::default().fg(Color::Cyan)),
+        Span::styled(
+            format!("{}|", app.palette_search_query),
+            Style::default().fg(Color::Rgb(69, 137, 255)),
+        ),
+    ]))];
+
+    if !app.palette_search_query.is_empty() && app.palette_search_results.is_empty() {
+        items.push(ListItem::new(Span::styled("      No matches", dim)));
+    }
+
+    for (i, result) in app.palette_search_results.iter().enumerate() {
+        let style = if i == app.palette_search_selected {
+            Style::default()
+                .fg(Color::Rgb(224, 224, 224))
+                .bg(selection_bg(app))
+        } else {
+            Style::default().fg(Color::Rgb(198, 198, 198))
+        };
+        let title = if result.title.is_empty() {
+            result.path.clone()
+        } else {
+            result.title.clone()
+        };
+        items.push(ListItem::new(Span::styled(
+            format!("      {}", title),
+            style,
+        )));
+    }
+    items
+}
+
+/// Draws the popup listing the URLs of the previewed note.
+fn draw_link_picker(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref picker) = app.link_picker else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    let title = if picker.wikilinks {
+        "Follow Wikilink (Enter to select note, Esc to close)"
+    } else {
+        "Open Link (Enter to open, Esc to close)"
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
+    let items: Vec<ListItem> = picker
+        .links
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            let style = if i == picker.selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(69, 137, 255))
+            };
+            ListItem::new(Span::styled(link.clone(), style))
+        })
+        .collect();
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(List::new(items).block(block), popup_area);
+}
+
+/// Draws the "insert link into" picker: a query line above the matching notes.
+fn draw_link_inserter(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref inserter) = app.link_inserter else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    let source = std::path::Path::new(&inserter.source)
+        .file_stem()
+        .map_or_else(
+            || inserter.source.clone(),
+            |s| s.to_string_lossy().to_string(),
+        );
+    let block = Block::default()
+        .title(format!(
+            "Link {} into... (Enter to append, Esc to close)",
+            source
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let inner = block.inner(popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Rgb(120, 120, 120))),
+        Span::styled(
+            inserter.query.clone(),
+            Style::default().fg(Color::Rgb(224, 224, 224)),
+        ),
+        Span::styled("█", Style::default().fg(Color::Rgb(120, 120, 120))),
+    ]));
+    let items: Vec<ListItem> = inserter
+        .results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let style = if i == inserter.selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(224, 224, 224))
+            };
+            ListItem::new(Span::styled(result.title.clone(), style))
+        })
+        .collect();
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(input, chunks[0]);
+    frame.render_widget(List::new(items), chunks[1]);
+}
+
+/// Draws the one-line text prompt as a small box over the current screen.
+pub fn draw_text_prompt(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref prompt) = app.text_prompt else {
+        return;
+    };
+    let width = area.width.saturating_mul(3) / 5;
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    let block = Block::default()
+        .title(format!("{} (Enter to confirm, Esc to cancel)", prompt.title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled(
+            prompt.input.clone(),
+            Style::default().fg(Color::Rgb(224, 224, 224)),
+        ),
+        Span::styled("█", Style::default().fg(Color::Rgb(120, 120, 120))),
+    ]))
+    .block(block);
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(input, popup_area);
+}
+
+/// Draws a pending confirmation as a small box over the current screen.
+pub fn draw_confirmation(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref confirmation) = app.confirmation else {
+        return;
+    };
+    let type_yes = app.settings.confirm_style == ConfirmStyle::TypeYes;
+    let width = area.width.saturating_mul(3) / 5;
+    let height = if type_yes { 4 } else { 3 };
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(area.height),
+    };
+    let title = if type_yes {
+        "Confirm (type yes and Enter, Esc to cancel)"
+    } else {
+        "Confirm (y to go ahead, any other key cancels)"
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let mut lines = vec![Line::from(Span::styled(
+        confirmation.prompt.clone(),
+        Style::default().fg(Color::Rgb(224, 224, 224)),
+    ))];
+    if type_yes {
+        lines.push(Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Rgb(120, 120, 120))),
+            Span::styled(
+                confirmation.input.clone(),
+                Style::default().fg(Color::Rgb(224, 224, 224)),
+            ),
+            Span::styled("█", Style::default().fg(Color::Rgb(120, 120, 120))),
+        ]));
+    }
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+/// Draws the saved searches picker, each name followed by its query.
+pub fn draw_saved_searches(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(selected) = app.saved_search_selected else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    let block = Block::default()
+        .title("Saved Searches (Enter to run, Esc to close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
     let items: Vec<ListItem> = app
-        .command_items
+        .settings
+        .saved_searches
         .iter()
         .enumerate()
-        .map(|(i, cmd)| {
-            let style = if i == app.selected_command_index {
+        .map(|(i, search)| {
+            let style = if i == selected {
                 Style::default()
                     .fg(Color::Black)
                     .bg(Color::Yellow)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(Color::Rgb(224, 224, 224))
             };
-            let content = format!("{} - {}", cmd.name, cmd.description);
-            ListItem::new(Span::styled(content, style))
+            ListItem::new(Line::from(vec![
+                Span::styled(search.name.clone(), style),
+                Span::styled(
+                    format!("  {}", search.query),
+                    Style::default().fg(Color::Rgb(120, 120, 120)),
+                ),
+            ]))
         })
         .collect();
 
-    let list = List::new(items).block(block);
-    frame.render_widget(list, inner_area);
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(List::new(items).block(block), popup_area);
+}
+
+/// Draws the tag browser: tags with their note counts on the left, and the
+/// first notes carrying the highlighted tag on the right.
+pub fn draw_tag_browser(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref browser) = app.tag_browser else {
+        return;
+    };
+    let popup_area = centered_rect(70, 60, area);
+    let block = Block::default()
+        .title("Tags (Enter to list notes, Esc to close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let inner = block.inner(popup_area);
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(block, popup_area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(inner);
+
+    let dim = Style::default().fg(Color::Rgb(120, 120, 120));
+    let items: Vec<ListItem> = browser
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(i, (tag, notes))| {
+            let style = if i == browser.selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(224, 224, 224))
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("#{}", tag), style),
+                Span::styled(format!("  {}", notes.len()), dim),
+            ]))
+        })
+        .collect();
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(browser.selected));
+    frame.render_stateful_widget(List::new(items), columns[0], &mut state);
+
+    let Some((tag, notes)) = browser.tags.get(browser.selected) else {
+        return;
+    };
+    let shown = columns[1].height.saturating_sub(2) as usize;
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Notes tagged #{}", tag),
+            Style::default()
+                .fg(Color::Rgb(150, 150, 150))
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::default(),
+    ];
+    lines.extend(notes.iter().take(shown).map(|path| {
+        let title = std::path::Path::new(path)
+            .file_stem()
+            .map_or_else(|| path.clone(), |s| s.to_string_lossy().to_string());
+        Line::from(Span::styled(
+            title,
+            Style::default().fg(Color::Rgb(198, 198, 198)),
+        ))
+    }));
+    if notes.len() > shown && shown > 0 {
+        lines.pop();
+        lines.push(Line::from(Span::styled(
+            format!("… and {} more", notes.len() - shown + 1),
+            dim,
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), columns[1]);
+}
+
+/// Draws the link graph popup: outbound links, then backlinks, each under a heading.
+fn draw_link_graph(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref graph) = app.link_graph else {
+        return;
+    };
+    let popup_area = centered_rect(60, 60, area);
+    let title = std::path::Path::new(&graph.path)
+        .file_stem()
+        .map_or_else(|| graph.path.clone(), |s| s.to_string_lossy().to_string());
+    let block = Block::default()
+        .title(format!(
+            "Links of {} (Enter to select, Esc to close)",
+            title
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
+    let heading_style = Style::default()
+        .fg(Color::Rgb(150, 150, 150))
+        .add_modifier(Modifier::BOLD);
+    let sections = [
+        ("Links to", &graph.outbound, 0),
+        ("Linked from", &graph.inbound, graph.outbound.len()),
+    ];
+    let mut items = Vec::new();
+    let mut selected_row = 0;
+    for (heading, notes, offset) in sections {
+        items.push(ListItem::new(Span::styled(
+            format!("{} ({})", heading, notes.len()),
+            heading_style,
+        )));
+        for (i, note) in notes.iter().enumerate() {
+            let style = if offset + i == graph.selected {
+                selected_row = items.len();
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(224, 224, 224))
+            };
+            items.push(ListItem::new(Span::styled(format!("  {}", note), style)));
+        }
+    }
+
+    // Keep the selection visible in long neighbourhoods
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(selected_row));
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_stateful_widget(List::new(items).block(block), popup_area, &mut state);
+}
+
+/// Draws the quick look overlay, a bigger preview than the side pane.
+fn draw_quick_look(app: &mut App, frame: &mut Frame, area: Rect) {
+    let Some(ref mut quick_look) = app.quick_look else {
+        return;
+    };
+    let popup_area = centered_rect(90, 90, area);
+    let title = if quick_look.matches.is_empty() {
+        format!(
+            "{} (j/k to scroll, Esc or Space to close)",
+            quick_look.title
+        )
+    } else {
+        format!(
+            "{} (match {}/{}, n/N for next/previous, j/k to scroll, Esc or Space to close)",
+            quick_look.title,
+            quick_look.current_match + 1,
+            quick_look.matches.len()
+        )
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::horizontal(1))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
+    // Scroll counts wrapped rows; estimate them from display width, as the preview does
+    if let Some(target) = quick_look.jump_to.take() {
+        let width = (block.inner(popup_area).width as usize).max(1);
+        let row: usize = quick_look.lines[..target]
+            .iter()
+            .map(|line| line.width().div_ceil(width).max(1))
+            .sum();
+        // Keep a couple of lines of context above the match
+        quick_look.scroll = row.saturating_sub(2) as u16;
+    }
+    let paragraph = Paragraph::new(quick_look.lines.clone())
+        .style(Style::default().fg(Color::Rgb(224, 224, 224)))
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((quick_look.scroll, 0));
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Draws the read-only config file overlay.
+pub fn draw_config_preview(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref preview) = app.config_preview else {
+        return;
+    };
+    let popup_area = centered_rect(80, 80, area);
+    let block = Block::default()
+        .title(format!(
+            "{} (e to edit, j/k to scroll, Esc to close)",
+            preview.path
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let paragraph = Paragraph::new(preview.lines.clone())
+        .block(block)
+        .scroll((preview.scroll, 0));
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Draws the timings of the last benchmark run.
+pub fn draw_benchmark_report(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(ref report) = app.benchmark_report else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    let block = Block::default()
+        .title("Benchmark (any key to close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::horizontal(1))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+    let label_width = report
+        .iter()
+        .map(|(label, _)| label.width())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = report
+        .iter()
+        .map(|(label, timing)| {
+            let value = match timing {
+                Ok(elapsed) => Span::styled(
+                    format!("{:.1?}", elapsed),
+                    Style::default().fg(Color::Rgb(224, 224, 224)),
+                ),
+                Err(e) => Span::styled(format!("failed: {}", e), Style::default().fg(Color::Red)),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", label, width = label_width),
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                value,
+            ])
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Renders the previewed note, reusing the lines from the last frame while
+/// the note's text and the render options stay the same.
+fn cached_render(
+    app: &mut App,
+    path: &str,
+    content: &str,
+    code_width: Option<usize>,
+) -> RenderedMarkdown {
+    let options = RenderOptions {
+        code_width,
+        ..app.render_options()
+    };
+    let cached = app
+        .preview_content
+        .as_ref()
+        .filter(|cached| cached.path == path);
+    let Some(cached) = cached else {
+        // Still loading
+        return render_markdown(content, options);
+    };
+    if let Some(ref render) = cached.rendered {
+        if render.rendered_with(&options) {
+            return render.markdown.clone();
+        }
+    }
+    let rendered = render_markdown(content, options);
+    let render = CachedRender::new(options, rendered.clone());
+    if let Some(ref mut cached) = app.preview_content {
+        cached.rendered = Some(render);
+    }
+    rendered
+}
+
+/// Draws the "send to" menu listing the configured external tools.
+fn draw_send_to_menu(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(selected) = app.send_to_selected else {
+        return;
+    };
+    let popup_area = centered_rect(50, 40, area);
+    let block = Block::default()
+        .title("Send To (Enter to run, Esc to close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .style(Style::default().bg(Color::Rgb(22, 22, 22)));
+
+    let items: Vec<ListItem> = app
+        .settings
+        .send_to
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            let style = if i == selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(224, 224, 224))
+            };
+            ListItem::new(Span::styled(target.name.clone(), style))
+        })
+        .collect();
+
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(List::new(items).block(block), popup_area);
 }
 
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -403,3 +1440,341 @@ pub fn draw_vector_indexing_ui(app: &App, frame: &mut Frame, area: Rect) {
     // Render the paragraph in the inner area
     frame.render_widget(paragraph, inner_area);
 }
+
+/// Previews the highlighted related note next to the related-files list.
+fn draw_related_preview(app: &mut App, frame: &mut Frame, area: Rect) {
+    let Some(result) = app.related_files.get(app.related_selected) else {
+        return;
+    };
+    let path = result.path.clone();
+    let title = result.title.clone();
+
+    // Render once per note rather than on every frame
+    let cached = matches!(&app.related_preview, Some((cached, _)) if *cached == path);
+    if !cached {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => format!("Error reading file: {}", e),
+        };
+        let lines = render_markdown(&content, app.render_options()).lines;
+        app.related_preview = Some((path, lines));
+    }
+    let Some((_, ref lines)) = app.related_preview else {
+        return;
+    };
+
+    let block = Block::default()
+        .title(format!("Preview: {}", title))
+        .padding(Padding {
+            left: 1,
+            right: 2,
+            top: 1,
+            bottom: 1,
+        })
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(Color::Rgb(70, 70, 70)));
+    let preview = Paragraph::new(lines.clone())
+        .style(
+            Style::default()
+                .fg(Color::Rgb(224, 224, 224))
+                .bg(Color::Rgb(38, 38, 38)),
+        )
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, area);
+}
+
+/// Cuts `text` to at most `max_width` terminal columns, ending with `…` when shortened.
+/// Wide (e.g. CJK) characters count as two columns and are never split.
+fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    // Leave one column for the ellipsis
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > max_width - 1 {
+            break;
+        }
+        truncated.push(c);
+        width += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Padding of the preview pane: the configured margins, widened on both sides
+/// to center the text when the pane is wider than `preview_max_width`.
+fn preview_padding(app: &App, pane_width: u16) -> Padding {
+    let x = app.settings.preview_padding_x;
+    let y = app.settings.preview_padding_y;
+    let text_width = pane_width.saturating_sub(2 * x);
+    let extra = match app.settings.preview_max_width {
+        Some(max) if max > 0 && text_width > max => text_width - max,
+        _ => 0,
+    };
+    Padding {
+        left: x + extra / 2,
+        right: x + extra - extra / 2,
+        top: y,
+        bottom: y,
+    }
+}
+
+/// One-line summary of a note's file: absolute path, size, modification time and tags.
+fn metadata_line(path: &str, content: &str) -> Line<'static> {
+    let dim = Style::default().fg(Color::Rgb(120, 120, 120));
+    let absolute =
+        fs::canonicalize(path).map_or_else(|_| path.to_string(), |p| p.display().to_string());
+    let mut spans = vec![Span::styled(absolute, dim)];
+    if let Ok(meta) = fs::metadata(path) {
+        spans.push(Span::styled(
+            format!("  {} bytes", format_count(meta.len() as usize)),
+            dim,
+        ));
+        if let Ok(modified) = meta.modified() {
+            let modified: chrono::DateTime<chrono::Local> = modified.into();
+            spans.push(Span::styled(
+                format!("  modified {}", modified.format("%Y-%m-%d %H:%M")),
+                dim,
+            ));
+        }
+    }
+    let tags = crate::app::frontmatter::tags(content);
+    if !tags.is_empty() {
+        let tags = tags
+            .iter()
+            .map(|tag| format!("#{}", tag))
+            .collect::<Vec<_>>()
+            .join(" ");
+        spans.push(Span::styled(
+            format!("  {}", tags),
+            Style::default().fg(Color::Rgb(187, 134, 252)),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Tags as `#tag` chips fitting in `width` columns, ending in `+N` for the ones
+/// that didn't fit. The gaps between chips take the row's `row_style`.
+fn tag_chips(tags: &[String], width: usize, row_style: Style) -> Vec<Span<'static>> {
+    let chip_style = Style::default()
+        .fg(Color::Rgb(187, 134, 252))
+        .bg(Color::Rgb(45, 35, 60));
+    let mut chips = Vec::new();
+    let mut used = 0;
+    for (i, tag) in tags.iter().enumerate() {
+        let chip = format!("#{}", tag);
+        let left = tags.len() - i - 1;
+        // Leave room for the "+N" of the tags after this one
+        let reserve = if left > 0 {
+            format!(" +{}", left).len()
+        } else {
+            0
+        };
+        if used + chip.width() + 1 + reserve > width {
+            let more = format!("+{}", tags.len() - i);
+            if used + more.len() + 1 <= width {
+                chips.push(Span::styled(" ", row_style));
+                chips.push(Span::styled(more, row_style.fg(Color::Rgb(120, 120, 120))));
+            }
+            break;
+        }
+        used += chip.width() + 1;
+        chips.push(Span::styled(" ", row_style));
+        chips.push(Span::styled(chip, chip_style));
+    }
+    chips
+}
+
+/// Colors results are marked with when `colorize_by_folder` is on.
+const FOLDER_COLORS: [Color; 8] = [
+    Color::Rgb(69, 137, 255),
+    Color::Rgb(242, 166, 92),
+    Color::Rgb(187, 134, 252),
+    Color::Rgb(102, 204, 153),
+    Color::Rgb(255, 111, 145),
+    Color::Rgb(255, 214, 102),
+    Color::Rgb(77, 208, 225),
+    Color::Rgb(170, 170, 170),
+];
+
+/// The first folder of `path` below the vault root, if the note isn't at the top.
+fn top_level_folder(root: &std::path::Path, path: &str) -> Option<String> {
+    let relative = std::path::Path::new(path).strip_prefix(root).ok()?;
+    let mut components = relative.components();
+    let first = components.next()?;
+    // A single component is the file itself
+    components.next()?;
+    Some(first.as_os_str().to_string_lossy().to_string())
+}
+
+/// A color derived from the folder name alone, so it stays the same between runs.
+fn folder_color(folder: &str) -> Color {
+    // FNV-1a; std's hashers make no promise of stable output
+    let hash = folder.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    FOLDER_COLORS[(hash % FOLDER_COLORS.len() as u64) as usize]
+}
+
+/// Parses an `accent_color` setting. `vault` picks a color from the name of
+/// `vault_root`, so each vault keeps its own; None when that isn't known yet.
+pub fn accent_color(setting: &str, vault_root: &std::path::Path) -> Option<Color> {
+    if setting == "vault" {
+        let name = vault_root.file_name()?.to_string_lossy();
+        return Some(folder_color(&name));
+    }
+    let hex = setting.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Background of the selected row in a list: the accent darkened enough for
+/// light text to stay readable on it.
+fn selection_bg(app: &App) -> Color {
+    match app.accent {
+        Some(Color::Rgb(r, g, b)) => {
+            let darken = |c: u8| (c as u16 * 7 / 10) as u8;
+            Color::Rgb(darken(r), darken(g), darken(b))
+        }
+        Some(color) => color,
+        None => Color::Rgb(70, 130, 180),
+    }
+}
+
+/// The tinted background of each major screen, for `state_tints`.
+pub fn state_background(state: AppState) -> Option<Color> {
+    match state {
+        AppState::Preview => Some(Color::Rgb(25, 23, 20)),
+        AppState::Search => Some(Color::Rgb(19, 22, 28)),
+        AppState::CommandPalette => Some(Color::Rgb(25, 20, 28)),
+        AppState::IndexingVectors => Some(Color::Rgb(19, 26, 21)),
+        _ => None,
+    }
+}
+
+/// Repaints cells of `area` that use the default background with `tint`.
+pub fn tint_background(frame: &mut Frame, area: Rect, tint: Color) {
+    let buffer = frame.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buffer[(x, y)];
+            if cell.bg == Color::Rgb(22, 22, 22) {
+                cell.set_bg(tint);
+            }
+        }
+    }
+}
+
+/// Header badge telling whether related files for the previewed note are up to date.
+fn index_status_span(status: IndexStatus) -> Span<'static> {
+    match status {
+        IndexStatus::NoSnapshot => Span::styled(
+            "[no index snapshot]",
+            Style::default().fg(Color::Rgb(120, 120, 120)),
+        ),
+        IndexStatus::Indexed(at) => Span::styled(
+            format!("[indexed {}]", format_age(at)),
+            Style::default().fg(Color::Rgb(120, 120, 120)),
+        ),
+        IndexStatus::Stale(at) => Span::styled(
+            format!("[changed since indexed {}]", format_age(at)),
+            Style::default().fg(Color::Rgb(255, 204, 0)),
+        ),
+    }
+}
+
+/// Rough age of a timestamp, e.g. `5m ago` or `3d ago`.
+fn format_age(time: std::time::SystemTime) -> String {
+    let secs = time.elapsed().map_or(0, |age| age.as_secs());
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+/// Formats a count with thousands separators, e.g. `5000` -> `"5,000"`.
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+pub fn draw_indexing_ui(app: &App, frame: &mut Frame, area: Rect) {
+    // Fill the background so the popup sits on the usual dark canvas
+    frame.render_widget(
+        Block::default().style(Style::default().bg(Color::Rgb(22, 22, 22))),
+        area,
+    );
+
+    let popup_area = centered_rect(50, 30, area);
+    let block = Block::default()
+        .title("Building Search Index")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner_area = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(inner_area);
+
+    let spinner = app.spinner_chars[app.spinner_idx];
+    let status = Line::from(vec![
+        Span::styled(format!("{} ", spinner), Style::default().fg(Color::Yellow)),
+        Span::styled(
+            "Building search index...",
+            Style::default().fg(Color::White),
+        ),
+    ]);
+    frame.render_widget(
+        Paragraph::new(status).alignment(ratatui::layout::Alignment::Center),
+        chunks[0],
+    );
+
+    // notemancy-core indexes everything in one call without reporting progress,
+    // so there is no gauge; the note count and elapsed time show it is working
+    let elapsed = app.indexing_started.elapsed().as_secs();
+    let detail = if app.indexing_total > 0 {
+        format!(
+            "{} documents, {}s elapsed",
+            format_count(app.indexing_total),
+            elapsed
+        )
+    } else {
+        format!("{}s elapsed", elapsed)
+    };
+    frame.render_widget(
+        Paragraph::new(detail)
+            .style(Style::default().fg(Color::Rgb(150, 150, 150)))
+            .alignment(ratatui::layout::Alignment::Center),
+        chunks[2],
+    );
+}
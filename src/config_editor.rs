@@ -3,23 +3,150 @@ use crossterm::terminal::disable_raw_mode;
 use notemancy_core::config;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io::Stdout;
+use std::path::PathBuf;
 
 /// Opens an arbitrary file in the default editor (using $EDITOR or "vi").
 /// It restores the terminal, launches the editor for the given path, then reinitializes the terminal.
+/// Returns whether the file was modified while the editor was open.
+/// With `read_only`, vi, vim and nvim are started with `-R`; other editors can't be told.
 pub fn open_file_in_editor(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     path: &str,
-) -> Result<()> {
+    read_only: bool,
+) -> Result<bool> {
+    let modified = || {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    };
+    let before = modified();
     // Restore terminal state so the editor can work.
     ratatui::restore();
     disable_raw_mode()?;
     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-    let _ = std::process::Command::new(editor).arg(path).status();
+    let mut command = std::process::Command::new(&editor);
+    let program = std::path::Path::new(&editor)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    if read_only && matches!(program, "vi" | "vim" | "nvim") {
+        command.arg("-R");
+    }
+    let _ = command.arg(path).status();
     // Reinitialize terminal.
     *terminal = ratatui::init();
+    Ok(modified() != before)
+}
+
+/// Runs a shell command line with the terminal handed over, like `open_file_in_editor`.
+pub fn run_external_command(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    command: &str,
+) -> Result<()> {
+    ratatui::restore();
+    disable_raw_mode()?;
+    let status = if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+            .args(["/C", command])
+            .status()
+    } else {
+        std::process::Command::new("sh")
+            .args(["-c", command])
+            .status()
+    };
+    *terminal = ratatui::init();
+    let status = status?;
+    if !status.success() {
+        return Err(color_eyre::eyre::eyre!(
+            "`{}` exited with {}",
+            command,
+            status
+        ));
+    }
+    Ok(())
+}
+
+/// Opens a URL in the system browser without blocking or touching the terminal.
+pub fn open_url(url: &str) -> Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
     Ok(())
 }
 
+/// Locates the notemancy config file, e.g. `~/.config/notemancy/config.yaml`.
+/// notemancy_core doesn't expose its config path, so the same directory is probed here.
+pub fn config_path() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join("notemancy");
+    ["config.yaml", "config.yml", "config.toml"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// The vault directory from the notemancy config: the deepest directory named in the
+/// config file that holds every note in `note_paths`. notemancy_core doesn't expose its
+/// config fields, so every path-like value in the file is tried.
+pub fn vault_root(note_paths: &[String]) -> Option<PathBuf> {
+    if note_paths.is_empty() {
+        return None;
+    }
+    let config = load_yaml()?;
+    let mut candidates = Vec::new();
+    collect_dirs(&config, &mut candidates);
+    candidates
+        .into_iter()
+        .filter(|dir| {
+            note_paths
+                .iter()
+                .all(|note| std::path::Path::new(note).starts_with(dir))
+        })
+        .max_by_key(|dir| dir.components().count())
+}
+
+/// The `theme` from the notemancy config, if it sets one.
+pub fn config_theme() -> Option<String> {
+    load_yaml()?.get("theme")?.as_str().map(str::to_string)
+}
+
+/// Parses the notemancy config file as YAML, the format notemancy_core writes.
+fn load_yaml() -> Option<serde_yaml::Value> {
+    let contents = std::fs::read_to_string(config_path()?).ok()?;
+    serde_yaml::from_str(&contents).ok()
+}
+
+/// Collects every string in `value` that names an existing directory, expanding `~/`.
+fn collect_dirs(value: &serde_yaml::Value, found: &mut Vec<PathBuf>) {
+    match value {
+        serde_yaml::Value::String(text) => {
+            let path = match text.strip_prefix("~/").zip(dirs::home_dir()) {
+                Some((rest, home)) => home.join(rest),
+                None => PathBuf::from(text),
+            };
+            if path.is_absolute() && path.is_dir() {
+                found.push(path);
+            }
+        }
+        serde_yaml::Value::Sequence(items) => {
+            items.iter().for_each(|item| collect_dirs(item, found));
+        }
+        serde_yaml::Value::Mapping(map) => {
+            map.values().for_each(|item| collect_dirs(item, found));
+        }
+        serde_yaml::Value::Tagged(tagged) => collect_dirs(&tagged.value, found),
+        _ => {}
+    }
+}
+
 /// (Your existing open_config_in_editor remains unchanged.)
 pub fn open_config_in_editor(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
     ratatui::restore();
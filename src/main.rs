@@ -2,25 +2,54 @@ pub mod app;
 pub mod config_editor;
 
 use app::core::App;
-use color_eyre::eyre::Report;
 use color_eyre::Result;
-use notemancy_core::search::init_search_engine;
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+
+/// Note paths given as arguments or piped in one per line (`rg -l foo | notemancy-console`).
+/// Relative paths are taken from the working directory; missing files are dropped.
+fn input_note_paths() -> Vec<String> {
+    let mut paths: Vec<String> = std::env::args().skip(1).collect();
+    if !std::io::stdin().is_terminal() {
+        paths.extend(std::io::stdin().lock().lines().map_while(|line| line.ok()));
+    }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(|path| cwd.join(PathBuf::from(path)))
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .collect()
+}
 
 fn main() -> Result<()> {
     // Install color-eyre for improved error reports.
     color_eyre::install()?;
 
-    // Initialize the search engine
-    let search_engine = init_search_engine()
-        .map_err(|e| Report::msg(format!("Failed to initialize search engine: {}", e)))?;
-    println!("Search engine initialized successfully");
+    // `Scanner::from_config` takes no thread count, and rayon sizes its global pool
+    // from RAYON_NUM_THREADS once, so `scan_threads` is applied before any thread starts.
+    if let Ok(settings) = app::settings::Settings::load() {
+        if settings.scan_threads.is_some() {
+            std::env::set_var(
+                "RAYON_NUM_THREADS",
+                settings.scan_thread_count().to_string(),
+            );
+        }
+    }
+
+    // Read piped input before the terminal takes over; keys then come from the tty.
+    let note_paths = input_note_paths();
 
     // Initialize the terminal using ratatui's helper.
     let mut terminal = ratatui::init();
 
-    // Create the app and inject the search engine.
+    // Create the app; it initializes the search engine itself once on screen.
     let mut app = App::new();
-    app.set_search_engine(search_engine);
+    if !note_paths.is_empty() {
+        app.seed_results(note_paths);
+    }
 
     // Run the app.
     let result = app.run(&mut terminal);